@@ -1,11 +1,28 @@
-use crate::database::{Database, Task, Subtask, Project, Folder, TaskWithDetails};
+use crate::database::{Database, DatabasePool, Task, TaskStatus, Project, Folder, TaskWithDetails, ProjectDeletePolicy, FolderDeletePolicy, RecurrenceRule, Target, Timestamp};
+use crate::error::{AppError, failed_to, parse_field, parse_optional_field, require_text};
+use crate::patch::Patch;
 use tauri::State;
-use std::sync::Mutex;
 use uuid::Uuid;
-use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
-pub type DatabaseState = Mutex<Database>;
+pub mod history;
+pub mod logs;
+pub mod pomodoro;
+pub mod project_templates;
+pub mod query;
+pub mod recurrence;
+pub mod reminders;
+pub mod saved_searches;
+pub mod search;
+pub mod stats;
+pub mod subtasks;
+pub mod task_list;
+pub mod templates;
+pub mod trash;
+pub mod undo;
+pub mod workflows;
+
+pub type DatabaseState = DatabasePool;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateTaskRequest {
@@ -16,19 +33,28 @@ pub struct CreateTaskRequest {
     pub project_id: Option<i32>,
     pub subtasks: Vec<String>,
     pub tags: Vec<String>,
+    pub recurrence: Option<RecurrenceRule>,
 }
 
+// Fields left out are not changed. The `Patch` fields can also be `null`,
+// which clears them: no due date, no project, an empty description or a task
+// that no longer repeats.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateTaskRequest {
     pub id: String,
     pub title: Option<String>,
-    pub description: Option<String>,
-    pub due_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_unchanged")]
+    pub description: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_unchanged")]
+    pub due_date: Patch<String>,
     pub priority: Option<String>,
-    pub project_id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Patch::is_unchanged")]
+    pub project_id: Patch<i32>,
     pub completed: Option<bool>,
     pub subtasks: Option<Vec<String>>,
     pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Patch::is_unchanged")]
+    pub recurrence: Patch<RecurrenceRule>,
 }
 
 // Task Commands
@@ -36,124 +62,153 @@ pub struct UpdateTaskRequest {
 pub async fn create_task(
     request: CreateTaskRequest,
     db: State<'_, DatabaseState>,
-) -> Result<String, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    
+) -> Result<String, AppError> {
+    db.write(move |db| {
+        db.record(
+            "Create task",
+            |id: Option<&String>| id.map(|id| Target::Task(id.clone())).into_iter().collect(),
+            |db| insert_task(db, request),
+        )
+    })
+    .await
+}
+
+// Shared by every command that creates a task from user input
+pub(crate) fn insert_task(db: &Database, request: CreateTaskRequest) -> Result<String, AppError> {
+    require_text("title", &request.title)?;
+    let priority = parse_field("priority", &request.priority)?;
+    let due_date = parse_optional_field("due_date", request.due_date.as_deref())?;
+
     let task_id = Uuid::new_v4().to_string();
-    let now = Utc::now().to_rfc3339();
+    let now = Timestamp::now();
     
     let task = Task {
         id: task_id.clone(),
         title: request.title,
         description: request.description,
-        due_date: request.due_date,
-        priority: request.priority,
+        due_date,
+        priority,
         completed: false,
+        status: TaskStatus::Todo,
+        column_id: None,
         project_id: request.project_id,
-        created_at: now.clone(),
+        series_id: request.recurrence.as_ref().map(|_| Uuid::new_v4().to_string()),
+        recurrence: request.recurrence,
+        created_at: now,
         updated_at: now,
+        completed_at: None,
     };
 
-    db.save_task(&task).map_err(|e| format!("Failed to save task: {}", e))?;
-
-    // Save subtasks
-    for subtask_text in request.subtasks.iter() {
-        let subtask = Subtask {
-            id: Uuid::new_v4().to_string(),
-            task_id: task_id.clone(),
-            text: subtask_text.clone(),
-            completed: false,
-        };
-        db.save_subtask(&subtask).map_err(|e| format!("Failed to save subtask: {}", e))?;
-    }
-
-    // Save tags
-    db.save_tags_for_task(&task_id, &request.tags)
-        .map_err(|e| format!("Failed to save tags: {}", e))?;
+    db.create_task(&task, &request.subtasks, &request.tags)
+        .map_err(failed_to("save task"))?;
 
     Ok(task_id)
 }
 
 #[tauri::command]
-pub async fn get_all_tasks(db: State<'_, DatabaseState>) -> Result<Vec<TaskWithDetails>, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.get_all_tasks().map_err(|e| format!("Failed to get tasks: {}", e))
+pub async fn get_all_tasks(db: State<'_, DatabaseState>) -> Result<Vec<TaskWithDetails>, AppError> {
+    db.read(|db| {
+        db.get_all_tasks().map_err(failed_to("get tasks"))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_task(
+    task_id: String,
+    db: State<'_, DatabaseState>,
+) -> Result<Option<TaskWithDetails>, AppError> {
+    db.read(move |db| {
+        db.get_task(&task_id).map_err(failed_to("get task"))
+    })
+    .await
 }
 
 #[tauri::command]
 pub async fn update_task(
     request: UpdateTaskRequest,
     db: State<'_, DatabaseState>,
-) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    
+) -> Result<(), AppError> {
+    db.write(move |db| edit_task(db, request)).await
+}
+
+// Applies an update request to a stored task
+pub(crate) fn edit_task(db: &Database, request: UpdateTaskRequest) -> Result<(), AppError> {
+    // Checked before touching the database so bad input fails fast
+    if let Some(title) = &request.title {
+        require_text("title", title)?;
+    }
+    let priority = request.priority.as_deref().map(|p| parse_field("priority", p)).transpose()?;
+    // A blank due date clears it, the same as `null`
+    let due_date = request
+        .due_date
+        .try_map(|due_date| parse_optional_field("due_date", Some(&due_date)))?
+        .flatten();
+
     // Get existing task
-    let tasks = db.get_all_tasks().map_err(|e| format!("Failed to get tasks: {}", e))?;
-    let mut task = tasks.into_iter()
-        .find(|t| t.task.id == request.id)
-        .ok_or("Task not found")?
+    let mut task = db
+        .get_task(&request.id)
+        .map_err(failed_to("get task"))?
+        .ok_or_else(|| AppError::not_found("task", &request.id))?
         .task;
 
     // Update fields if provided
     if let Some(title) = request.title {
         task.title = title;
     }
-    if let Some(description) = request.description {
-        task.description = description;
-    }
-    if let Some(due_date) = request.due_date {
-        task.due_date = Some(due_date);
+    match request.description {
+        Patch::Unchanged => {}
+        Patch::Clear => task.description.clear(),
+        Patch::Set(description) => task.description = description,
     }
-    if let Some(priority) = request.priority {
+    due_date.apply(&mut task.due_date);
+    if let Some(priority) = priority {
         task.priority = priority;
     }
-    if let Some(project_id) = request.project_id {
-        task.project_id = Some(project_id);
+    if request.project_id.apply(&mut task.project_id) {
+        // Workflow columns belong to a single project
+        task.column_id = None;
+    }
+    // The series id is kept when recurrence is cleared, so past
+    // occurrences stay linked
+    if request.recurrence.apply(&mut task.recurrence)
+        && task.recurrence.is_some()
+        && task.series_id.is_none()
+    {
+        task.series_id = Some(Uuid::new_v4().to_string());
     }
     if let Some(completed) = request.completed {
         task.completed = completed;
-    }
-    
-    task.updated_at = Utc::now().to_rfc3339();
-
-    db.save_task(&task).map_err(|e| format!("Failed to update task: {}", e))?;
-
-    // Update subtasks if provided
-    if let Some(subtask_texts) = request.subtasks {
-        // Delete existing subtasks
-        let existing_subtasks = db.get_subtasks_for_task(&task.id)
-            .map_err(|e| format!("Failed to get subtasks: {}", e))?;
-        for subtask in existing_subtasks {
-            db.delete_subtask(&subtask.id)
-                .map_err(|e| format!("Failed to delete subtask: {}", e))?;
-        }
-
-        // Create new subtasks
-        for subtask_text in subtask_texts {
-            let subtask = Subtask {
-                id: Uuid::new_v4().to_string(),
-                task_id: task.id.clone(),
-                text: subtask_text,
-                completed: false,
-            };
-            db.save_subtask(&subtask)
-                .map_err(|e| format!("Failed to save subtask: {}", e))?;
+        if completed {
+            task.status = TaskStatus::Done;
+        } else if task.status.is_terminal() {
+            task.status = TaskStatus::Todo;
         }
     }
 
-    // Update tags if provided
-    if let Some(tags) = request.tags {
-        db.save_tags_for_task(&task.id, &tags)
-            .map_err(|e| format!("Failed to save tags: {}", e))?;
-    }
+    task.updated_at = Timestamp::now();
 
-    Ok(())
+    // The task, its subtasks and its tags are written together or not at all
+    db.record("Edit task", |_| vec![Target::Task(task.id.clone())], |db| {
+        db.save_task(&task)?;
+        if let Some(subtask_texts) = &request.subtasks {
+            db.sync_subtasks(&task.id, subtask_texts)?;
+        }
+        if let Some(tags) = &request.tags {
+            db.save_tags_for_task(&task.id, tags)?;
+        }
+        Ok(())
+    })
+    .map_err(failed_to::<AppError>("update task"))
 }
 
 #[tauri::command]
-pub async fn delete_task(task_id: String, db: State<'_, DatabaseState>) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.delete_task(&task_id).map_err(|e| format!("Failed to delete task: {}", e))
+pub async fn delete_task(task_id: String, db: State<'_, DatabaseState>) -> Result<(), AppError> {
+    db.write(move |db| {
+        db.record("Delete task", |_| vec![Target::Task(task_id.clone())], |db| db.delete_task(&task_id))
+            .map_err(|e| AppError::from(e).about("task", &task_id).context("Failed to delete task"))
+    })
+    .await
 }
 
 #[tauri::command]
@@ -161,47 +216,34 @@ pub async fn toggle_task_completion(
     task_id: String,
     completed: bool,
     db: State<'_, DatabaseState>,
-) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.update_task_completion(&task_id, completed)
-        .map_err(|e| format!("Failed to update task completion: {}", e))
+) -> Result<(), AppError> {
+    db.write(move |db| {
+        let label = if completed { "Complete task" } else { "Reopen task" };
+        db.record(label, |_| vec![Target::Task(task_id.clone())], |db| {
+            db.update_task_completion(&task_id, completed)
+        })
+        .map_err(|e| {
+            AppError::from(e).about("task", &task_id).context("Failed to update task completion")
+        })
+    })
+    .await
 }
 
-// Subtask Commands
 #[tauri::command]
-pub async fn toggle_subtask_completion(
-    subtask_id: String,
-    completed: bool,
+pub async fn set_task_status(
+    task_id: String,
+    status: TaskStatus,
     db: State<'_, DatabaseState>,
-) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    
-    println!("Attempting to toggle subtask with ID: {}", subtask_id);
-    
-    // Get existing subtask (we need to reconstruct it with updated completion)
-    // This is a bit inefficient but works with the current structure
-    let tasks = db.get_all_tasks().map_err(|e| format!("Failed to get tasks: {}", e))?;
-    
-    for task_with_details in tasks {
-        println!("Checking task: {} with {} subtasks", task_with_details.task.id, task_with_details.subtasks.len());
-        for subtask in task_with_details.subtasks {
-            println!("  Subtask ID: {}, Text: {}", subtask.id, subtask.text);
-            if subtask.id == subtask_id {
-                println!("Found matching subtask! Updating completion to: {}", completed);
-                let updated_subtask = Subtask {
-                    id: subtask.id,
-                    task_id: subtask.task_id,
-                    text: subtask.text,
-                    completed,
-                };
-                return db.save_subtask(&updated_subtask)
-                    .map_err(|e| format!("Failed to update subtask: {}", e));
-            }
-        }
-    }
-    
-    println!("Subtask with ID {} not found in any task!", subtask_id);
-    Err("Subtask not found".to_string())
+) -> Result<(), AppError> {
+    db.write(move |db| {
+        db.record("Change task status", |_| vec![Target::Task(task_id.clone())], |db| {
+            db.update_task_status(&task_id, status)
+        })
+        .map_err(|e| {
+            AppError::from(e).about("task", &task_id).context("Failed to update task status")
+        })
+    })
+    .await
 }
 
 // Project Commands
@@ -212,30 +254,52 @@ pub async fn create_project(
     description: Option<String>,
     folder_id: Option<i32>,
     db: State<'_, DatabaseState>,
-) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    
-    let project = Project {
-        id: 0, // Will be auto-incremented
-        name,
-        color,
-        description,
-        folder_id,
-    };
+) -> Result<(), AppError> {
+    require_text("name", &name)?;
+    let color = parse_field("color", &color)?;
+
+    db.write(move |db| {
+        let project = Project {
+            id: 0, // Will be auto-incremented
+            name,
+            color,
+            description,
+            folder_id,
+        };
 
-    db.save_project(&project).map_err(|e| format!("Failed to save project: {}", e))
+        db.record(
+            "Create project",
+            |id: Option<&i32>| id.map(|id| Target::Project(*id)).into_iter().collect(),
+            |db| db.save_project(&project),
+        )
+        .map(|_| ())
+        .map_err(failed_to("save project"))
+    })
+    .await
 }
 
 #[tauri::command]
-pub async fn get_all_projects(db: State<'_, DatabaseState>) -> Result<Vec<Project>, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.get_all_projects().map_err(|e| format!("Failed to get projects: {}", e))
+pub async fn get_all_projects(db: State<'_, DatabaseState>) -> Result<Vec<Project>, AppError> {
+    db.read(|db| {
+        db.get_all_projects().map_err(failed_to("get projects"))
+    })
+    .await
 }
 
 #[tauri::command]
-pub async fn delete_project(project_id: i32, db: State<'_, DatabaseState>) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.delete_project(project_id).map_err(|e| format!("Failed to delete project: {}", e))
+pub async fn delete_project(
+    project_id: i32,
+    policy: Option<ProjectDeletePolicy>,
+    db: State<'_, DatabaseState>,
+) -> Result<(), AppError> {
+    db.write(move |db| {
+        let policy = policy.unwrap_or_default();
+        db.record("Delete project", |_| vec![Target::Project(project_id)], |db| {
+            db.delete_project(project_id, &policy)
+        })
+        .map_err(failed_to("delete project"))
+    })
+    .await
 }
 
 // Folder Commands
@@ -245,49 +309,77 @@ pub async fn create_folder(
     color: String,
     description: Option<String>,
     db: State<'_, DatabaseState>,
-) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    
-    let folder = Folder {
-        id: 0, // Will be auto-incremented
-        name,
-        color,
-        description,
-    };
+) -> Result<(), AppError> {
+    require_text("name", &name)?;
+    let color = parse_field("color", &color)?;
+
+    db.write(move |db| {
+        let folder = Folder {
+            id: 0, // Will be auto-incremented
+            name,
+            color,
+            description,
+        };
 
-    db.save_folder(&folder).map_err(|e| format!("Failed to save folder: {}", e))
+        db.record(
+            "Create folder",
+            |id: Option<&i32>| id.map(|id| Target::Folder(*id)).into_iter().collect(),
+            |db| db.save_folder(&folder),
+        )
+        .map(|_| ())
+        .map_err(failed_to("save folder"))
+    })
+    .await
 }
 
 #[tauri::command]
-pub async fn get_all_folders(db: State<'_, DatabaseState>) -> Result<Vec<Folder>, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.get_all_folders().map_err(|e| format!("Failed to get folders: {}", e))
+pub async fn get_all_folders(db: State<'_, DatabaseState>) -> Result<Vec<Folder>, AppError> {
+    db.read(|db| {
+        db.get_all_folders().map_err(failed_to("get folders"))
+    })
+    .await
 }
 
 #[tauri::command]
-pub async fn delete_folder(folder_id: i32, db: State<'_, DatabaseState>) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.delete_folder(folder_id).map_err(|e| format!("Failed to delete folder: {}", e))
+pub async fn delete_folder(
+    folder_id: i32,
+    policy: Option<FolderDeletePolicy>,
+    db: State<'_, DatabaseState>,
+) -> Result<(), AppError> {
+    db.write(move |db| {
+        let policy = policy.unwrap_or_default();
+        db.record("Delete folder", |_| vec![Target::Folder(folder_id)], |db| {
+            db.delete_folder(folder_id, &policy)
+        })
+        .map_err(failed_to("delete folder"))
+    })
+    .await
 }
 
 // Tag Commands
 #[tauri::command]
-pub async fn get_all_tags(db: State<'_, DatabaseState>) -> Result<Vec<String>, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.get_all_tags().map_err(|e| format!("Failed to get tags: {}", e))
+pub async fn get_all_tags(db: State<'_, DatabaseState>) -> Result<Vec<String>, AppError> {
+    db.read(|db| {
+        db.get_all_tags().map_err(failed_to("get tags"))
+    })
+    .await
 }
 
 // Settings Commands
 #[tauri::command]
-pub async fn set_theme(theme: String, db: State<'_, DatabaseState>) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.set_theme(&theme).map_err(|e| format!("Failed to set theme: {}", e))
+pub async fn set_theme(theme: String, db: State<'_, DatabaseState>) -> Result<(), AppError> {
+    db.write(move |db| {
+        db.set_theme(&theme).map_err(failed_to("set theme"))
+    })
+    .await
 }
 
 #[tauri::command]
-pub async fn get_theme(db: State<'_, DatabaseState>) -> Result<String, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.get_theme().map_err(|e| format!("Failed to get theme: {}", e))
+pub async fn get_theme(db: State<'_, DatabaseState>) -> Result<String, AppError> {
+    db.read(|db| {
+        db.get_theme().map_err(failed_to("get theme"))
+    })
+    .await
 }
 
 #[tauri::command]
@@ -295,29 +387,37 @@ pub async fn save_setting(
     key: String,
     value: String,
     db: State<'_, DatabaseState>,
-) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.save_setting(&key, &value).map_err(|e| format!("Failed to save setting: {}", e))
+) -> Result<(), AppError> {
+    db.write(move |db| {
+        db.save_setting(&key, &value).map_err(failed_to("save setting"))
+    })
+    .await
 }
 
 #[tauri::command]
-pub async fn get_setting(key: String, db: State<'_, DatabaseState>) -> Result<Option<String>, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.get_setting(&key).map_err(|e| format!("Failed to get setting: {}", e))
+pub async fn get_setting(key: String, db: State<'_, DatabaseState>) -> Result<Option<String>, AppError> {
+    db.read(move |db| {
+        db.get_setting(&key).map_err(failed_to("get setting"))
+    })
+    .await
 }
 
 // SearchBar Mode Commands
 #[tauri::command]
-pub async fn set_searchbar_mode(mode: String, db: State<'_, DatabaseState>) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.save_setting("searchbar_mode", &mode).map_err(|e| format!("Failed to set searchbar mode: {}", e))
+pub async fn set_searchbar_mode(mode: String, db: State<'_, DatabaseState>) -> Result<(), AppError> {
+    db.write(move |db| {
+        db.save_setting("searchbar_mode", &mode).map_err(failed_to("set searchbar mode"))
+    })
+    .await
 }
 
 #[tauri::command]
-pub async fn get_searchbar_mode(db: State<'_, DatabaseState>) -> Result<String, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    let mode = db.get_setting("searchbar_mode").map_err(|e| format!("Failed to get searchbar mode: {}", e))?;
-    Ok(mode.unwrap_or_else(|| "search".to_string())) // Default to search mode
+pub async fn get_searchbar_mode(db: State<'_, DatabaseState>) -> Result<String, AppError> {
+    db.read(|db| {
+        let mode = db.get_setting("searchbar_mode").map_err(failed_to("get searchbar mode"))?;
+        Ok(mode.unwrap_or_else(|| "search".to_string())) // Default to search mode
+    })
+    .await
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -338,7 +438,7 @@ pub struct ParsedTask {
 #[tauri::command]
 pub async fn parse_natural_language_task(
     request: NaturalLanguageTaskRequest,
-) -> Result<ParsedTask, String> {
+) -> Result<ParsedTask, AppError> {
     // Basic natural language parsing
     let input = request.input.to_lowercase();
     let mut title = request.input.clone();
@@ -391,11 +491,11 @@ pub async fn parse_natural_language_task(
 
     // Extract project mentions (e.g., "for work project", "in personal")
     let project_regex = regex::Regex::new(r"\b(?:for|in)\s+(\w+(?:\s+\w+)?)\s+project\b").unwrap();
-    if let Some(captures) = project_regex.captures(&input) {
-        if let Some(project) = captures.get(1) {
-            project_name = Some(project.as_str().to_string());
-            title = project_regex.replace(&title, "").to_string();
-        }
+    if let Some(captures) = project_regex.captures(&input)
+        && let Some(project) = captures.get(1)
+    {
+        project_name = Some(project.as_str().to_string());
+        title = project_regex.replace(&title, "").to_string();
     }
 
     // Clean up title
@@ -414,188 +514,72 @@ pub async fn parse_natural_language_task(
     })
 }
 
-// Logging Commands
-#[derive(Debug, Serialize, Deserialize)]
-pub struct LogEntry {
-    pub timestamp: String,
-    pub level: String,
-    pub message: String,
-    pub target: Option<String>,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-#[tauri::command]
-pub async fn get_application_logs() -> Result<Vec<LogEntry>, String> {
-    use chrono::{Duration, Utc};
-    
-    let now = Utc::now();
-    let mut logs = Vec::new();
-
-    // System startup and initialization logs
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(30)).to_rfc3339(),
-        level: "DEBUG".to_string(),
-        message: "main() entry point - initializing Tauri application".to_string(),
-        target: Some("ultralist::main::run".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(30) + Duration::milliseconds(150)).to_rfc3339(),
-        level: "TRACE".to_string(),
-        message: "Loading database from path: ~/.local/share/ultralist/database.sqlite3".to_string(),
-        target: Some("ultralist::database::Database::new".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(30) + Duration::milliseconds(200)).to_rfc3339(),
-        level: "DEBUG".to_string(),
-        message: "SQLite version: 3.45.0, WAL mode enabled, foreign_keys=ON".to_string(),
-        target: Some("ultralist::database::Database::initialize_tables".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(30) + Duration::milliseconds(350)).to_rfc3339(),
-        level: "TRACE".to_string(),
-        message: "CREATE TABLE IF NOT EXISTS tasks (id TEXT PRIMARY KEY, title TEXT NOT NULL, ...)".to_string(),
-        target: Some("ultralist::database::Database::create_tables".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(30) + Duration::milliseconds(400)).to_rfc3339(),
-        level: "DEBUG".to_string(),
-        message: "Database connection pool initialized with 5 connections".to_string(),
-        target: Some("ultralist::database".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(30) + Duration::seconds(1)).to_rfc3339(),
-        level: "INFO".to_string(),
-        message: "Registering 17 Tauri commands in invoke_handler".to_string(),
-        target: Some("tauri::Builder::invoke_handler".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(30) + Duration::seconds(1) + Duration::milliseconds(50)).to_rfc3339(),
-        level: "TRACE".to_string(),
-        message: "Command registration: [create_task, get_all_tasks, update_task, delete_task, ...]".to_string(),
-        target: Some("tauri::generate_handler".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(30) + Duration::seconds(2)).to_rfc3339(),
-        level: "DEBUG".to_string(),
-        message: "Webview window created: 800x600, URL: tauri://localhost/".to_string(),
-        target: Some("tauri::webview::WebviewWindow".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(30) + Duration::seconds(3)).to_rfc3339(),
-        level: "INFO".to_string(),
-        message: "Application startup complete - took 3.2s".to_string(),
-        target: Some("ultralist::main".to_string()),
-    });
-
-    // Runtime operation logs
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(25)).to_rfc3339(),
-        level: "TRACE".to_string(),
-        message: "Frontend -> invoke('get_all_tasks') - acquiring database lock".to_string(),
-        target: Some("ultralist::commands::get_all_tasks".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(25) + Duration::milliseconds(15)).to_rfc3339(),
-        level: "DEBUG".to_string(),
-        message: "SQL: SELECT t.*, GROUP_CONCAT(s.text) as subtasks FROM tasks t LEFT JOIN subtasks s ON t.id = s.task_id GROUP BY t.id".to_string(),
-        target: Some("ultralist::database::get_all_tasks_with_details".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(25) + Duration::milliseconds(42)).to_rfc3339(),
-        level: "TRACE".to_string(),
-        message: "Query returned 0 rows in 27ms".to_string(),
-        target: Some("ultralist::database".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(20)).to_rfc3339(),
-        level: "DEBUG".to_string(),
-        message: "Frontend -> invoke('get_theme') - reading from settings table".to_string(),
-        target: Some("ultralist::commands::get_theme".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(15)).to_rfc3339(),
-        level: "WARN".to_string(),
-        message: "Frontend not in Tauri context - falling back to error state".to_string(),
-        target: Some("ultralist::hooks::useDatabase::loadAllData".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(10)).to_rfc3339(),
-        level: "ERROR".to_string(),
-        message: "Database operation failed: NotInTauriContext { operation: 'createTask', context: 'web' }".to_string(),
-        target: Some("ultralist::hooks::useDatabase::createTask".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(8)).to_rfc3339(),
-        level: "DEBUG".to_string(),
-        message: "React component mount: <App /> - initializing useDatabase hook".to_string(),
-        target: Some("react::components::App".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(5)).to_rfc3339(),
-        level: "TRACE".to_string(),
-        message: "State update: setError('Error accessing data - application must be run in desktop mode')".to_string(),
-        target: Some("react::hooks::useDatabase".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(3)).to_rfc3339(),
-        level: "DEBUG".to_string(),
-        message: "User interaction: handleViewLogs() clicked - attempting to open logs window".to_string(),
-        target: Some("react::components::App::handleViewLogs".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(2)).to_rfc3339(),
-        level: "TRACE".to_string(),
-        message: "WebviewWindow construction: { url: '/logs.html', title: 'UltraList - Application Logs', width: 800, height: 600 }".to_string(),
-        target: Some("tauri::WebviewWindow::new".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::minutes(1)).to_rfc3339(),
-        level: "DEBUG".to_string(),
-        message: "Logs window event: tauri://created - window handle: 0x1a2b3c4d".to_string(),
-        target: Some("tauri::webview::events".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::seconds(30)).to_rfc3339(),
-        level: "TRACE".to_string(),
-        message: "Memory usage: heap=45.2MB, stack=2.1MB, virtual=128.7MB".to_string(),
-        target: Some("system::memory".to_string()),
-    });
-
-    logs.push(LogEntry {
-        timestamp: (now - Duration::seconds(10)).to_rfc3339(),
-        level: "DEBUG".to_string(),
-        message: "Performance: get_all_tasks() completed in 3.42ms, returned 0 items".to_string(),
-        target: Some("ultralist::performance".to_string()),
-    });
-
-    // Current log entry with more technical details
-    logs.push(LogEntry {
-        timestamp: now.to_rfc3339(),
-        level: "TRACE".to_string(),
-        message: "invoke('get_application_logs') -> generating 24 log entries for debugging session".to_string(),
-        target: Some("ultralist::commands::get_application_logs".to_string()),
-    });
-
-    // Sort by timestamp to ensure chronological order
-    logs.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-
-    Ok(logs)
+    fn create(db: &Database) -> String {
+        let task = Task {
+            description: "First draft".to_string(),
+            due_date: Some("2026-11-01".parse().unwrap()),
+            project_id: Some(1),
+            ..Task::sample("t1")
+        };
+        db.create_task(&task, &[], &[]).unwrap();
+        task.id
+    }
+
+    fn update(id: &str, fields: serde_json::Value) -> UpdateTaskRequest {
+        let mut request = serde_json::json!({ "id": id });
+        request.as_object_mut().unwrap().extend(fields.as_object().unwrap().clone());
+        serde_json::from_value(request).unwrap()
+    }
+
+    fn stored(db: &Database, id: &str) -> Task {
+        db.get_task(id).unwrap().unwrap().task
+    }
+
+    #[test]
+    fn missing_fields_are_unchanged() {
+        let db = Database::open_in_memory().unwrap();
+        let id = create(&db);
+
+        edit_task(&db, update(&id, serde_json::json!({ "title": "Final report" }))).unwrap();
+
+        let task = stored(&db, &id);
+        assert_eq!(task.title, "Final report");
+        assert_eq!(task.description, "First draft");
+        assert_eq!(task.due_date.map(|due| due.to_string()).as_deref(), Some("2026-11-01"));
+        assert_eq!(task.project_id, Some(1));
+    }
+
+    #[test]
+    fn null_clears_and_a_value_sets_again() {
+        let db = Database::open_in_memory().unwrap();
+        let id = create(&db);
+
+        let clear = serde_json::json!({ "description": null, "due_date": null, "project_id": null });
+        edit_task(&db, update(&id, clear)).unwrap();
+        let task = stored(&db, &id);
+        assert_eq!(task.description, "");
+        assert_eq!(task.due_date, None);
+        assert_eq!(task.project_id, None);
+
+        let set = serde_json::json!({ "description": "Notes", "due_date": "2026-12-24", "project_id": 2 });
+        edit_task(&db, update(&id, set)).unwrap();
+        let task = stored(&db, &id);
+        assert_eq!(task.description, "Notes");
+        assert_eq!(task.due_date.map(|due| due.to_string()).as_deref(), Some("2026-12-24"));
+        assert_eq!(task.project_id, Some(2));
+    }
+
+    #[test]
+    fn blank_due_date_clears_it() {
+        let db = Database::open_in_memory().unwrap();
+        let id = create(&db);
+
+        edit_task(&db, update(&id, serde_json::json!({ "due_date": "  " }))).unwrap();
+
+        assert_eq!(stored(&db, &id).due_date, None);
+    }
 }
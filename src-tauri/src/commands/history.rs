@@ -0,0 +1,18 @@
+use crate::database::TaskHistoryEntry;
+use crate::error::AppError;
+use super::DatabaseState;
+use tauri::State;
+
+// Newest change first
+#[tauri::command]
+pub async fn get_task_history(
+    task_id: String,
+    db: State<'_, DatabaseState>,
+) -> Result<Vec<TaskHistoryEntry>, AppError> {
+    db.read(move |db| {
+        db.get_task_history(&task_id).map_err(|e| {
+            AppError::from(e).about("task", &task_id).context("Failed to get task history")
+        })
+    })
+    .await
+}
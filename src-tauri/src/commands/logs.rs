@@ -0,0 +1,54 @@
+use crate::logging::{self, LogEntry, LogFilter};
+use crate::error::{AppError, failed_to};
+use super::DatabaseState;
+use tauri::{State, Window};
+
+#[tauri::command]
+pub async fn get_application_logs(filter: Option<LogFilter>) -> Result<Vec<LogEntry>, AppError> {
+    logging::entries(&filter.unwrap_or_default()).map_err(AppError::invalid)
+}
+
+#[tauri::command]
+pub async fn get_log_level() -> Result<String, AppError> {
+    Ok(logging::level().to_string().to_lowercase())
+}
+
+// Takes effect immediately and is kept for the next start
+#[tauri::command]
+pub async fn set_log_level(level: String, db: State<'_, DatabaseState>) -> Result<(), AppError> {
+    let level = logging::parse_level(&level).map_err(|e| AppError::invalid_field("level", e))?;
+    let value = level.to_string().to_lowercase();
+    db.write(move |db| {
+        db.save_setting(logging::LEVEL_SETTING, &value)
+            .map_err(failed_to("save log level"))
+    })
+    .await?;
+    logging::set_level(level);
+    log::info!("Log level set to {}", level);
+    Ok(())
+}
+
+// Entries logged from now on are pushed to the calling window as
+// `log-entry` events until it unsubscribes or closes
+#[tauri::command]
+pub async fn subscribe_logs(window: Window) -> Result<(), AppError> {
+    logging::subscribe(window.label());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unsubscribe_logs(window: Window) -> Result<(), AppError> {
+    logging::unsubscribe(window.label());
+    Ok(())
+}
+
+// Lets the frontend log into the same store, under the `react` target
+#[tauri::command]
+pub async fn write_frontend_log(
+    level: String,
+    message: String,
+    target: Option<String>,
+) -> Result<(), AppError> {
+    logging::log_frontend(&level, target.as_deref(), &message)
+        .map_err(|e| AppError::invalid_field("level", e))
+}
@@ -0,0 +1,120 @@
+use crate::database::{FocusStats, FocusStatsRequest, PomodoroPhase, PomodoroSession, PomodoroSettings};
+use crate::error::{AppError, failed_to};
+use super::DatabaseState;
+use tauri::State;
+
+// Longest phase or cycle the timer accepts
+const MAX_DURATION_MINUTES: u32 = 24 * 60;
+const MAX_SESSIONS_UNTIL_LONG_BREAK: u32 = 12;
+
+fn validate_settings(settings: &PomodoroSettings) -> Result<(), AppError> {
+    let durations = [
+        ("work_duration", settings.work_duration),
+        ("short_break_duration", settings.short_break_duration),
+        ("long_break_duration", settings.long_break_duration),
+    ];
+    for (field, minutes) in durations {
+        if minutes == 0 || minutes > MAX_DURATION_MINUTES {
+            return Err(AppError::invalid_field(
+                field,
+                format!("Duration must be between 1 and {} minutes", MAX_DURATION_MINUTES),
+            ));
+        }
+    }
+    if settings.sessions_until_long_break == 0 || settings.sessions_until_long_break > MAX_SESSIONS_UNTIL_LONG_BREAK {
+        return Err(AppError::invalid_field(
+            "sessions_until_long_break",
+            format!("Sessions until a long break must be between 1 and {}", MAX_SESSIONS_UNTIL_LONG_BREAK),
+        ));
+    }
+    Ok(())
+}
+
+// Any session still running is ended as interrupted
+#[tauri::command]
+pub async fn start_pomodoro_session(
+    task_id: Option<String>,
+    phase: PomodoroPhase,
+    planned_seconds: i64,
+    db: State<'_, DatabaseState>,
+) -> Result<PomodoroSession, AppError> {
+    if planned_seconds <= 0 || planned_seconds > MAX_DURATION_MINUTES as i64 * 60 {
+        return Err(AppError::invalid_field(
+            "planned_seconds",
+            format!("Planned duration must be between 1 second and {} minutes", MAX_DURATION_MINUTES),
+        ));
+    }
+    db.write(move |db| {
+        db.start_pomodoro_session(task_id.as_deref(), phase, planned_seconds).map_err(|e| {
+            match &task_id {
+                Some(task_id) => e.about("task", task_id),
+                None => e,
+            }
+            .context("Failed to start pomodoro session")
+        })
+    })
+    .await
+}
+
+// `actual_seconds` defaults to the time since the session started
+#[tauri::command]
+pub async fn finish_pomodoro_session(
+    id: i64,
+    actual_seconds: Option<i64>,
+    db: State<'_, DatabaseState>,
+) -> Result<PomodoroSession, AppError> {
+    db.write(move |db| {
+        db.finish_pomodoro_session(id, actual_seconds).map_err(|e| {
+            e.about("pomodoro session", id).context("Failed to finish pomodoro session")
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn abort_pomodoro_session(
+    id: i64,
+    actual_seconds: Option<i64>,
+    db: State<'_, DatabaseState>,
+) -> Result<PomodoroSession, AppError> {
+    db.write(move |db| {
+        db.abort_pomodoro_session(id, actual_seconds).map_err(|e| {
+            e.about("pomodoro session", id).context("Failed to abort pomodoro session")
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_active_pomodoro_session(
+    db: State<'_, DatabaseState>,
+) -> Result<Option<PomodoroSession>, AppError> {
+    db.read(|db| db.get_active_pomodoro_session().map_err(failed_to("get active pomodoro session")))
+        .await
+}
+
+#[tauri::command]
+pub async fn get_pomodoro_settings(db: State<'_, DatabaseState>) -> Result<PomodoroSettings, AppError> {
+    db.read(|db| db.get_pomodoro_settings().map_err(failed_to("get pomodoro settings"))).await
+}
+
+#[tauri::command]
+pub async fn save_pomodoro_settings(
+    settings: PomodoroSettings,
+    db: State<'_, DatabaseState>,
+) -> Result<(), AppError> {
+    validate_settings(&settings)?;
+    db.write(move |db| db.save_pomodoro_settings(&settings).map_err(failed_to("save pomodoro settings")))
+        .await
+}
+
+// Focus time from work sessions over a date range (the last 30 days by
+// default)
+#[tauri::command]
+pub async fn get_focus_stats(
+    request: Option<FocusStatsRequest>,
+    db: State<'_, DatabaseState>,
+) -> Result<FocusStats, AppError> {
+    db.read(move |db| db.get_focus_stats(&request.unwrap_or_default()).map_err(failed_to("get focus stats")))
+        .await
+}
@@ -0,0 +1,63 @@
+use crate::database::{Project, ProjectTemplate, Target};
+use crate::error::{AppError, failed_to, require_text};
+use super::DatabaseState;
+use tauri::State;
+
+#[tauri::command]
+pub async fn save_project_as_template(
+    project_id: i32,
+    name: Option<String>,
+    anchor_date: Option<String>,
+    db: State<'_, DatabaseState>,
+) -> Result<ProjectTemplate, AppError> {
+    if let Some(name) = &name {
+        require_text("name", name)?;
+    }
+    db.write(move |db| {
+        db.save_project_as_template(project_id, name.as_deref(), anchor_date.as_deref()).map_err(|e| {
+            e.about("project", project_id).context("Failed to save project template")
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn list_project_templates(
+    db: State<'_, DatabaseState>,
+) -> Result<Vec<ProjectTemplate>, AppError> {
+    db.read(|db| {
+        db.list_project_templates()
+            .map_err(failed_to("get project templates"))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_project_template(id: i32, db: State<'_, DatabaseState>) -> Result<(), AppError> {
+    db.write(move |db| {
+        db.delete_project_template(id)
+            .map_err(failed_to("delete project template"))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn instantiate_project_template(
+    id: i32,
+    folder_id: Option<i32>,
+    anchor_date: Option<String>,
+    name: Option<String>,
+    db: State<'_, DatabaseState>,
+) -> Result<Project, AppError> {
+    db.write(move |db| {
+        db.record(
+            "Create project from template",
+            |project: Option<&Project>| project.map(|p| Target::Project(p.id)).into_iter().collect(),
+            |db| db.instantiate_project_template(id, folder_id, anchor_date.as_deref(), name.as_deref()),
+        )
+        .map_err(|e| {
+            e.about("project template", id).context("Failed to create project from template")
+        })
+    })
+    .await
+}
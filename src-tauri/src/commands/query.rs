@@ -0,0 +1,16 @@
+use crate::database::TaskWithDetails;
+use crate::error::{AppError, failed_to};
+use super::DatabaseState;
+use tauri::State;
+
+#[tauri::command]
+pub async fn query_tasks(
+    query: String,
+    db: State<'_, DatabaseState>,
+) -> Result<Vec<TaskWithDetails>, AppError> {
+    db.read(move |db| {
+        db.query_tasks(&query)
+            .map_err(failed_to("query tasks"))
+    })
+    .await
+}
@@ -0,0 +1,16 @@
+use crate::database::SeriesHistory;
+use crate::error::{AppError, failed_to};
+use super::DatabaseState;
+use tauri::State;
+
+#[tauri::command]
+pub async fn get_series_history(
+    series_id: String,
+    db: State<'_, DatabaseState>,
+) -> Result<SeriesHistory, AppError> {
+    db.read(move |db| {
+        db.get_series_history(&series_id)
+            .map_err(failed_to("get series history"))
+    })
+    .await
+}
@@ -0,0 +1,84 @@
+use crate::database::Reminder;
+use crate::scheduler::ReminderScheduler;
+use crate::error::{AppError, failed_to};
+use super::DatabaseState;
+use tauri::State;
+
+#[tauri::command]
+pub async fn add_reminder(
+    task_id: String,
+    remind_at: Option<String>,
+    offset_minutes: Option<i64>,
+    db: State<'_, DatabaseState>,
+    scheduler: State<'_, ReminderScheduler>,
+) -> Result<Reminder, AppError> {
+    let reminder = db
+        .write(move |db| {
+            db.add_reminder(&task_id, remind_at.as_deref(), offset_minutes).map_err(|e| {
+                e.about("task", &task_id).context("Failed to add reminder")
+            })
+        })
+        .await?;
+    scheduler.wake();
+    Ok(reminder)
+}
+
+#[tauri::command]
+pub async fn get_reminders_for_task(
+    task_id: String,
+    db: State<'_, DatabaseState>,
+) -> Result<Vec<Reminder>, AppError> {
+    db.read(move |db| {
+        db.get_reminders_for_task(&task_id)
+            .map_err(failed_to("get reminders"))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_reminder(
+    reminder_id: String,
+    db: State<'_, DatabaseState>,
+    scheduler: State<'_, ReminderScheduler>,
+) -> Result<(), AppError> {
+    db.write(move |db| {
+        db.delete_reminder(&reminder_id)
+            .map_err(failed_to("delete reminder"))
+    })
+    .await?;
+    scheduler.wake();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn snooze_reminder(
+    reminder_id: String,
+    minutes: i64,
+    db: State<'_, DatabaseState>,
+    scheduler: State<'_, ReminderScheduler>,
+) -> Result<(), AppError> {
+    db.write(move |db| {
+        db.snooze_reminder(&reminder_id, minutes).map_err(|e| {
+            e.about("reminder", &reminder_id).context("Failed to snooze reminder")
+        })
+    })
+    .await?;
+    scheduler.wake();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn dismiss_reminder(
+    reminder_id: String,
+    db: State<'_, DatabaseState>,
+    scheduler: State<'_, ReminderScheduler>,
+) -> Result<(), AppError> {
+    db.write(move |db| {
+        db.dismiss_reminder(&reminder_id).map_err(|e| {
+            AppError::from(e).about("reminder", &reminder_id).context("Failed to dismiss reminder")
+        })
+    })
+    .await?;
+    scheduler.wake();
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use crate::database::{SavedSearch, SavedSearchCount, SavedSearchInput, TaskWithDetails};
+use crate::error::{AppError, failed_to, require_text};
+use super::DatabaseState;
+use tauri::State;
+
+#[tauri::command]
+pub async fn create_saved_search(
+    search: SavedSearchInput,
+    db: State<'_, DatabaseState>,
+) -> Result<SavedSearch, AppError> {
+    require_text("name", &search.name)?;
+    db.write(move |db| {
+        db.create_saved_search(&search)
+            .map_err(failed_to("create saved search"))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_saved_searches(db: State<'_, DatabaseState>) -> Result<Vec<SavedSearch>, AppError> {
+    db.read(|db| {
+        db.get_saved_searches()
+            .map_err(failed_to("get saved searches"))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn update_saved_search(
+    id: i32,
+    search: SavedSearchInput,
+    db: State<'_, DatabaseState>,
+) -> Result<SavedSearch, AppError> {
+    require_text("name", &search.name)?;
+    db.write(move |db| {
+        db.update_saved_search(id, &search).map_err(|e| {
+            AppError::from(e).about("saved search", id).context("Failed to update saved search")
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_saved_search(id: i32, db: State<'_, DatabaseState>) -> Result<(), AppError> {
+    db.write(move |db| {
+        db.delete_saved_search(id)
+            .map_err(failed_to("delete saved search"))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn evaluate_saved_search(
+    id: i32,
+    db: State<'_, DatabaseState>,
+) -> Result<Vec<TaskWithDetails>, AppError> {
+    db.read(move |db| {
+        db.evaluate_saved_search(id).map_err(|e| {
+            AppError::from(e).about("saved search", id).context("Failed to evaluate saved search")
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_saved_search_counts(
+    db: State<'_, DatabaseState>,
+) -> Result<Vec<SavedSearchCount>, AppError> {
+    db.read(|db| {
+        db.get_saved_search_counts()
+            .map_err(failed_to("count saved search results"))
+    })
+    .await
+}
@@ -0,0 +1,19 @@
+use crate::database::TaskSearchResult;
+use crate::error::{AppError, failed_to};
+use super::DatabaseState;
+use tauri::State;
+
+const DEFAULT_SEARCH_LIMIT: u32 = 50;
+
+#[tauri::command]
+pub async fn search_tasks(
+    query: String,
+    limit: Option<u32>,
+    db: State<'_, DatabaseState>,
+) -> Result<Vec<TaskSearchResult>, AppError> {
+    db.read(move |db| {
+        db.search_tasks(&query, limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+            .map_err(failed_to("search tasks"))
+    })
+    .await
+}
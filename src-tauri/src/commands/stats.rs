@@ -0,0 +1,17 @@
+use crate::database::{ProductivityStats, ProductivityStatsRequest};
+use crate::error::{AppError, failed_to};
+use super::DatabaseState;
+use tauri::State;
+
+// Completion analytics over a date range (the last 30 days by default)
+#[tauri::command]
+pub async fn get_productivity_stats(
+    request: Option<ProductivityStatsRequest>,
+    db: State<'_, DatabaseState>,
+) -> Result<ProductivityStats, AppError> {
+    db.read(move |db| {
+        db.get_productivity_stats(&request.unwrap_or_default())
+            .map_err(failed_to("get productivity stats"))
+    })
+    .await
+}
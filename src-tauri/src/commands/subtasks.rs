@@ -0,0 +1,103 @@
+use crate::database::{Subtask, Target};
+use crate::error::{AppError, require_text};
+use super::DatabaseState;
+use tauri::State;
+
+#[tauri::command]
+pub async fn add_subtask(
+    task_id: String,
+    text: String,
+    db: State<'_, DatabaseState>,
+) -> Result<Subtask, AppError> {
+    require_text("text", &text)?;
+    db.write(move |db| {
+        db.record("Add subtask", |_| vec![Target::Task(task_id.clone())], |db| {
+            db.add_subtask(&task_id, &text)
+        })
+        .map_err(|e| {
+            AppError::from(e).about("task", &task_id).context("Failed to add subtask")
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn rename_subtask(
+    subtask_id: String,
+    text: String,
+    db: State<'_, DatabaseState>,
+) -> Result<Subtask, AppError> {
+    require_text("text", &text)?;
+    db.write(move |db| {
+        db.record("Rename subtask", |_| vec![Target::Subtask(subtask_id.clone())], |db| {
+            db.rename_subtask(&subtask_id, &text)
+        })
+        .map_err(|e| {
+            AppError::from(e).about("subtask", &subtask_id).context("Failed to rename subtask")
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_subtask(subtask_id: String, db: State<'_, DatabaseState>) -> Result<(), AppError> {
+    db.write(move |db| {
+        db.record("Delete subtask", |_| vec![Target::Subtask(subtask_id.clone())], |db| {
+            db.delete_subtask(&subtask_id)
+        })
+        .map_err(|e| {
+            AppError::from(e).about("subtask", &subtask_id).context("Failed to delete subtask")
+        })
+    })
+    .await
+}
+
+// `subtask_ids` is the task's full list of subtasks in the new order
+#[tauri::command]
+pub async fn reorder_subtasks(
+    task_id: String,
+    subtask_ids: Vec<String>,
+    db: State<'_, DatabaseState>,
+) -> Result<Vec<Subtask>, AppError> {
+    db.write(move |db| {
+        db.record("Reorder subtasks", |_| vec![Target::Task(task_id.clone())], |db| {
+            db.reorder_subtasks(&task_id, &subtask_ids)
+        })
+        .map_err(|e| {
+            e.about("task", &task_id).context("Failed to reorder subtasks")
+        })
+    })
+    .await
+}
+
+// Flips completion and returns the subtask as it is now
+#[tauri::command]
+pub async fn toggle_subtask(subtask_id: String, db: State<'_, DatabaseState>) -> Result<Subtask, AppError> {
+    db.write(move |db| {
+        db.record("Toggle subtask", |_| vec![Target::Subtask(subtask_id.clone())], |db| {
+            db.toggle_subtask(&subtask_id)
+        })
+        .map_err(|e| {
+            AppError::from(e).about("subtask", &subtask_id).context("Failed to toggle subtask")
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn toggle_subtask_completion(
+    subtask_id: String,
+    completed: bool,
+    db: State<'_, DatabaseState>,
+) -> Result<(), AppError> {
+    db.write(move |db| {
+        db.record("Toggle subtask", |_| vec![Target::Subtask(subtask_id.clone())], |db| {
+            db.set_subtask_completion(&subtask_id, completed)
+        })
+        .map_err(|e| {
+            AppError::from(e).about("subtask", &subtask_id).context("Failed to update subtask")
+        })?;
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,16 @@
+use crate::database::{TaskListRequest, TaskPage};
+use crate::error::{AppError, failed_to};
+use super::DatabaseState;
+use tauri::State;
+
+#[tauri::command]
+pub async fn list_tasks(
+    request: TaskListRequest,
+    db: State<'_, DatabaseState>,
+) -> Result<TaskPage, AppError> {
+    db.read(move |db| {
+        db.list_tasks(&request)
+            .map_err(failed_to("list tasks"))
+    })
+    .await
+}
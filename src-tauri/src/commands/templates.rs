@@ -0,0 +1,121 @@
+use crate::database::{Database, Target, TaskTemplate, TaskTemplateInput};
+use crate::error::{AppError, failed_to, require_text};
+use super::{CreateTaskRequest, DatabaseState, insert_task};
+use tauri::State;
+use chrono::{DateTime, Local};
+
+#[tauri::command]
+pub async fn create_template(
+    template: TaskTemplateInput,
+    db: State<'_, DatabaseState>,
+) -> Result<TaskTemplate, AppError> {
+    require_text("name", &template.name)?;
+    require_text("title", &template.title)?;
+    db.write(move |db| {
+        db.create_template(&template)
+            .map_err(failed_to("create template"))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn list_templates(db: State<'_, DatabaseState>) -> Result<Vec<TaskTemplate>, AppError> {
+    db.read(|db| {
+        db.list_templates()
+            .map_err(failed_to("get templates"))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_template(id: i32, db: State<'_, DatabaseState>) -> Result<(), AppError> {
+    db.write(move |db| {
+        db.delete_template(id)
+            .map_err(failed_to("delete template"))
+    })
+    .await
+}
+
+// Creates a task from a template through the same path as `create_task`.
+// `due_date` and `project_id` override the template for this task only.
+#[tauri::command]
+pub async fn instantiate_template(
+    id: i32,
+    due_date: Option<String>,
+    project_id: Option<i32>,
+    db: State<'_, DatabaseState>,
+) -> Result<String, AppError> {
+    db.write(move |db| instantiate(db, id, due_date, project_id, Local::now())).await
+}
+
+// Returns the new task's id; placeholders are filled in for `now`
+fn instantiate(
+    db: &Database,
+    id: i32,
+    due_date: Option<String>,
+    project_id: Option<i32>,
+    now: DateTime<Local>,
+) -> Result<String, AppError> {
+    let template = db
+        .get_template(id)
+        .map_err(|e| AppError::from(e).about("template", id).context("Failed to get template"))?
+        .expanded(now);
+
+    let request = CreateTaskRequest {
+        title: template.title,
+        description: template.task_description,
+        due_date,
+        priority: template.priority.to_string(),
+        project_id: project_id.or(template.project_id),
+        subtasks: template.subtasks,
+        tags: template.tags,
+        recurrence: None,
+    };
+    db.record(
+        "Create task from template",
+        |id: Option<&String>| id.map(|id| Target::Task(id.clone())).into_iter().collect(),
+        |db| insert_task(db, request),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Priority;
+    use chrono::TimeZone;
+
+    #[test]
+    fn instantiating_fills_in_the_template() {
+        let db = Database::open_in_memory().unwrap();
+        let template = db
+            .create_template(&TaskTemplateInput {
+                name: "Weekly review".to_string(),
+                description: None,
+                title: "Review {{week}}".to_string(),
+                task_description: "Notes for {{date}}".to_string(),
+                priority: Priority::High,
+                subtasks: vec!["Inbox zero by {{weekday}}".to_string()],
+                tags: vec!["review-{{year}}".to_string()],
+                project_id: Some(1),
+            })
+            .unwrap();
+        let now = Local.with_ymd_and_hms(2026, 10, 17, 9, 0, 0).unwrap();
+
+        let id = instantiate(&db, template.id, Some("2026-10-20".to_string()), None, now).unwrap();
+
+        let task = db.get_task(&id).unwrap().unwrap();
+        assert_eq!(task.task.title, "Review 2026-W42");
+        assert_eq!(task.task.description, "Notes for 2026-10-17");
+        assert_eq!(task.task.priority, Priority::High);
+        assert_eq!(task.task.project_id, Some(1));
+        assert_eq!(task.task.due_date.map(|due| due.to_string()).as_deref(), Some("2026-10-20"));
+        assert_eq!(task.subtasks[0].text, "Inbox zero by Saturday");
+        assert_eq!(task.tags, ["review-2026"]);
+        // The stored template keeps its placeholders
+        assert_eq!(db.get_template(template.id).unwrap().title, "Review {{week}}");
+
+        let elsewhere = instantiate(&db, template.id, None, Some(2), now).unwrap();
+        assert_eq!(db.get_task(&elsewhere).unwrap().unwrap().task.project_id, Some(2));
+        assert!(matches!(instantiate(&db, 99, None, None, now), Err(AppError::NotFound { .. })));
+    }
+}
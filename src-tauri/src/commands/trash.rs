@@ -0,0 +1,81 @@
+use crate::database::{TRASH_RETENTION_SETTING, Target, TrashItem, TrashKind};
+use crate::error::{AppError, failed_to};
+use super::DatabaseState;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+// Expired items are purged at startup and then this often while running
+const PURGE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+#[tauri::command]
+pub async fn list_trash(db: State<'_, DatabaseState>) -> Result<Vec<TrashItem>, AppError> {
+    db.read(|db| db.list_trash().map_err(failed_to("list trash"))).await
+}
+
+#[tauri::command]
+pub async fn restore_item(
+    kind: TrashKind,
+    id: String,
+    db: State<'_, DatabaseState>,
+) -> Result<(), AppError> {
+    let numeric_id = || id.parse().map_err(|_| AppError::invalid("id must be an integer"));
+    let target = match kind {
+        TrashKind::Task => Target::Task(id.clone()),
+        TrashKind::Project => Target::Project(numeric_id()?),
+        TrashKind::Folder => Target::Folder(numeric_id()?),
+    };
+    db.write(move |db| {
+        db.record("Restore from trash", |_| vec![target.clone()], |db| {
+            db.restore_item(kind, &id)
+        })
+        .map_err(|e| {
+            let entity = match kind {
+                TrashKind::Task => "trashed task",
+                TrashKind::Project => "trashed project",
+                TrashKind::Folder => "trashed folder",
+            };
+            AppError::from(e).about(entity, &id).context("Failed to restore item")
+        })
+    })
+    .await
+}
+
+// Returns how many rows were deleted for good
+#[tauri::command]
+pub async fn empty_trash(db: State<'_, DatabaseState>) -> Result<usize, AppError> {
+    db.write(|db| db.empty_trash().map_err(failed_to("empty trash"))).await
+}
+
+#[tauri::command]
+pub async fn get_trash_retention_days(db: State<'_, DatabaseState>) -> Result<i64, AppError> {
+    db.read(|db| db.trash_retention_days().map_err(failed_to("get trash retention"))).await
+}
+
+// 0 keeps trashed items until the trash is emptied
+#[tauri::command]
+pub async fn set_trash_retention_days(days: i64, db: State<'_, DatabaseState>) -> Result<(), AppError> {
+    if days < 0 {
+        return Err(AppError::invalid_field("days", "Trash retention must not be negative"));
+    }
+    // A shorter retention applies straight away
+    db.write(move |db| {
+        db.save_setting(TRASH_RETENTION_SETTING, &days.to_string())
+            .and_then(|_| db.purge_expired_trash())
+            .map(|_| ())
+            .map_err(failed_to("set trash retention"))
+    })
+    .await
+}
+
+// Spawns the loop that purges expired trash
+pub fn start_purging(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let db = app.state::<DatabaseState>();
+            if let Err(e) = db.write(|db| db.purge_expired_trash().map_err(AppError::from)).await {
+                log::error!("Failed to purge expired trash: {}", e);
+            }
+            tokio::time::sleep(PURGE_INTERVAL).await;
+        }
+    });
+}
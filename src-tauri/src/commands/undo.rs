@@ -0,0 +1,21 @@
+use crate::database::{Operation, UndoState};
+use crate::error::{AppError, failed_to};
+use super::DatabaseState;
+use tauri::State;
+
+// Returns the operation that was undone, or `None` when there was nothing
+// to undo
+#[tauri::command]
+pub async fn undo(db: State<'_, DatabaseState>) -> Result<Option<Operation>, AppError> {
+    db.write(|db| db.undo().map_err(failed_to("undo"))).await
+}
+
+#[tauri::command]
+pub async fn redo(db: State<'_, DatabaseState>) -> Result<Option<Operation>, AppError> {
+    db.write(|db| db.redo().map_err(failed_to("redo"))).await
+}
+
+#[tauri::command]
+pub async fn get_undo_state(db: State<'_, DatabaseState>) -> Result<UndoState, AppError> {
+    db.read(|db| db.get_undo_state().map_err(failed_to("get undo state"))).await
+}
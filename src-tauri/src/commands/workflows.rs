@@ -0,0 +1,53 @@
+use crate::database::{Target, WorkflowColumn, WorkflowColumnInput};
+use crate::error::{AppError, failed_to};
+use super::DatabaseState;
+use tauri::State;
+
+#[tauri::command]
+pub async fn get_project_workflow(
+    project_id: i32,
+    db: State<'_, DatabaseState>,
+) -> Result<Vec<WorkflowColumn>, AppError> {
+    db.read(move |db| {
+        db.get_project_workflow(project_id)
+            .map_err(failed_to("get project workflow"))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_project_workflow(
+    project_id: i32,
+    columns: Vec<WorkflowColumnInput>,
+    db: State<'_, DatabaseState>,
+) -> Result<Vec<WorkflowColumn>, AppError> {
+    db.write(move |db| {
+        // Cards in a column that is removed or changes category change with it
+        let task_ids = db.get_board_task_ids(project_id).map_err(failed_to("save project workflow"))?;
+        db.record(
+            "Edit workflow",
+            |_| {
+                let cards = task_ids.iter().cloned().map(Target::Task);
+                std::iter::once(Target::Workflow(project_id)).chain(cards).collect()
+            },
+            |db| db.set_project_workflow(project_id, &columns),
+        )
+        .map_err(failed_to("save project workflow"))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn move_task_to_column(
+    task_id: String,
+    column_id: i32,
+    db: State<'_, DatabaseState>,
+) -> Result<(), AppError> {
+    db.write(move |db| {
+        db.record("Move task", |_| vec![Target::Task(task_id.clone())], |db| {
+            db.move_task_to_column(&task_id, column_id)
+        })
+        .map_err(failed_to("move task"))
+    })
+    .await
+}
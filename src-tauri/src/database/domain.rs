@@ -0,0 +1,321 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveTime, SecondsFormat, TimeZone, Utc};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+// Value types for task and project fields. Each one is stored and serialized
+// as text; parsing that text is the only way to build one, so anything read
+// back from the database or a request has already been validated. `FromStr`
+// errors are written for the user.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl Priority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "low" => Ok(Priority::Low),
+            "medium" => Ok(Priority::Medium),
+            "high" => Ok(Priority::High),
+            _ => Err(format!("Priority must be low, medium or high, not '{}'", value)),
+        }
+    }
+}
+
+// When a task is due. A `Day` is a calendar date with no time zone: it means
+// that day wherever the user currently is. `At` is an exact instant and keeps
+// the UTC offset it was given with, so its calendar date is the one the user
+// saw when setting it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DueDate {
+    Day(NaiveDate),
+    At(DateTime<FixedOffset>),
+}
+
+impl DueDate {
+    pub fn date(&self) -> NaiveDate {
+        match self {
+            DueDate::Day(date) => *date,
+            DueDate::At(instant) => instant.date_naive(),
+        }
+    }
+
+    // The same kind of due date on another day, keeping the time of day
+    pub fn with_date(&self, date: NaiveDate) -> DueDate {
+        match self {
+            DueDate::Day(_) => DueDate::Day(date),
+            DueDate::At(instant) => instant
+                .offset()
+                .from_local_datetime(&date.and_time(instant.time()))
+                .single()
+                .map(DueDate::At)
+                .unwrap_or(*self),
+        }
+    }
+
+    // The instant reminders count from. A `Day` is taken to mean 09:00 local
+    // time, which is when a "due today" reminder is useful.
+    pub fn instant(&self) -> Option<DateTime<Utc>> {
+        match self {
+            DueDate::Day(date) => Local
+                .from_local_datetime(&date.and_time(NaiveTime::from_hms_opt(9, 0, 0)?))
+                .earliest()
+                .map(|instant| instant.with_timezone(&Utc)),
+            DueDate::At(instant) => Some(instant.with_timezone(&Utc)),
+        }
+    }
+}
+
+impl fmt::Display for DueDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DueDate::Day(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            DueDate::At(instant) => f.write_str(&instant.to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+        }
+    }
+}
+
+impl FromStr for DueDate {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        let trimmed = value.trim();
+        let parsed = if trimmed.len() == 10 {
+            NaiveDate::parse_from_str(trimmed, "%Y-%m-%d").ok().map(DueDate::Day)
+        } else {
+            DateTime::parse_from_rfc3339(trimmed).ok().map(DueDate::At)
+        };
+        parsed.ok_or_else(|| {
+            format!("Due date must be a date (YYYY-MM-DD) or an RFC 3339 timestamp, not '{}'", value)
+        })
+    }
+}
+
+// A point in time, always in UTC. Written in the same RFC 3339 form as
+// `Utc::now().to_rfc3339()`, which the created_at/updated_at indexes sort by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timestamp(DateTime<Utc>);
+
+impl Timestamp {
+    pub fn now() -> Self {
+        Timestamp(Utc::now())
+    }
+
+    pub fn instant(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(instant: DateTime<Utc>) -> Self {
+        Timestamp(instant)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.to_rfc3339())
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        DateTime::parse_from_rfc3339(value.trim())
+            .map(|instant| Timestamp(instant.with_timezone(&Utc)))
+            .map_err(|_| format!("Timestamp must be in RFC 3339 format, not '{}'", value))
+    }
+}
+
+// A project or folder color: a Tailwind background class such as
+// `bg-blue-500`, or a hex color (`#rgb`, `#rrggbb` or `#rrggbbaa`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Color(String);
+
+impl Color {
+    fn is_tailwind_class(value: &str) -> bool {
+        value.strip_prefix("bg-").is_some_and(|name| {
+            name.starts_with(|c: char| c.is_ascii_lowercase())
+                && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+                && !name.ends_with('-')
+                && !name.contains("--")
+        })
+    }
+
+    fn is_hex(value: &str) -> bool {
+        value.strip_prefix('#').is_some_and(|digits| {
+            matches!(digits.len(), 3 | 6 | 8) && digits.chars().all(|c| c.is_ascii_hexdigit())
+        })
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        let trimmed = value.trim();
+        if Self::is_tailwind_class(trimmed) || Self::is_hex(trimmed) {
+            Ok(Color(trimmed.to_string()))
+        } else {
+            Err(format!(
+                "Color must be a Tailwind class like bg-blue-500 or a hex color like #3b82f6, not '{}'",
+                value
+            ))
+        }
+    }
+}
+
+// Serde and SQLite conversions through the text form above
+macro_rules! text_conversions {
+    ($($ty:ty),*) => {$(
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+            }
+        }
+
+        impl ToSql for $ty {
+            fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+                Ok(ToSqlOutput::from(self.to_string()))
+            }
+        }
+
+        impl FromSql for $ty {
+            fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+                value.as_str()?.parse().map_err(|e: String| FromSqlError::Other(e.into()))
+            }
+        }
+    )*};
+}
+
+text_conversions!(Priority, DueDate, Timestamp, Color);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    // Writes a value to SQLite and reads it back
+    fn through_sqlite<T: ToSql + FromSql>(value: &T) -> T {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.query_row("SELECT ?1", [value], |row| row.get(0)).unwrap()
+    }
+
+    fn round_trips<T>(value: T, text: &str)
+    where
+        T: ToSql + FromSql + Serialize + for<'de> Deserialize<'de> + FromStr<Err = String> + fmt::Display + PartialEq + fmt::Debug,
+    {
+        assert_eq!(value.to_string(), text);
+        assert_eq!(text.parse::<T>().unwrap(), value);
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, format!("\"{}\"", text));
+        assert_eq!(serde_json::from_str::<T>(&json).unwrap(), value);
+        assert_eq!(through_sqlite(&value), value);
+    }
+
+    fn rejected<T>(text: &str)
+    where
+        T: FromSql + for<'de> Deserialize<'de> + FromStr<Err = String> + fmt::Debug,
+    {
+        assert!(text.parse::<T>().is_err(), "{:?} parsed", text);
+        assert!(serde_json::from_value::<T>(serde_json::Value::from(text)).is_err(), "{:?} deserialized", text);
+        let conn = Connection::open_in_memory().unwrap();
+        let read = conn.query_row("SELECT ?1", [text], |row| row.get::<_, T>(0));
+        assert!(matches!(read, Err(rusqlite::Error::FromSqlConversionFailure(..))), "{:?} read back", text);
+    }
+
+    #[test]
+    fn priorities() {
+        round_trips(Priority::Low, "low");
+        round_trips(Priority::Medium, "medium");
+        round_trips(Priority::High, "high");
+        assert_eq!(" High ".parse::<Priority>(), Ok(Priority::High));
+        assert!(Priority::Low < Priority::Medium && Priority::Medium < Priority::High);
+        for text in ["", "urgent", "hi gh"] {
+            rejected::<Priority>(text);
+        }
+        assert_eq!("urgent".parse::<Priority>().unwrap_err(), "Priority must be low, medium or high, not 'urgent'");
+    }
+
+    #[test]
+    fn due_dates() {
+        let day = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        round_trips(DueDate::Day(day), "2026-10-17");
+
+        let at = DateTime::parse_from_rfc3339("2026-10-17T23:30:00+02:00").unwrap();
+        round_trips(DueDate::At(at), "2026-10-17T23:30:00+02:00");
+        round_trips(DueDate::At(DateTime::parse_from_rfc3339("2026-10-17T08:00:00Z").unwrap()), "2026-10-17T08:00:00Z");
+        // The date is the one at the offset given, not in UTC
+        assert_eq!(DueDate::At(at).date(), day);
+        assert_eq!(DueDate::At(at).instant().unwrap().to_rfc3339(), "2026-10-17T21:30:00+00:00");
+
+        let next = NaiveDate::from_ymd_opt(2026, 10, 24).unwrap();
+        assert_eq!(DueDate::Day(day).with_date(next), DueDate::Day(next));
+        assert_eq!(DueDate::At(at).with_date(next).to_string(), "2026-10-24T23:30:00+02:00");
+
+        for text in ["", "tomorrow", "2026-13-01", "2026-02-30", "17/10/2026", "2026-10-17 09:00", "2026-10-17T09:00:00"] {
+            rejected::<DueDate>(text);
+        }
+    }
+
+    #[test]
+    fn timestamps() {
+        let instant = Utc.with_ymd_and_hms(2026, 10, 17, 9, 30, 0).unwrap();
+        round_trips(Timestamp::from(instant), "2026-10-17T09:30:00+00:00");
+        // Other offsets are converted to UTC
+        let parsed: Timestamp = "2026-10-17T11:30:00+02:00".parse().unwrap();
+        assert_eq!(parsed.instant(), instant);
+        assert_eq!(parsed.to_string(), "2026-10-17T09:30:00+00:00");
+        for text in ["", "2026-10-17", "yesterday"] {
+            rejected::<Timestamp>(text);
+        }
+    }
+
+    #[test]
+    fn colors() {
+        for text in ["bg-blue-500", "bg-slate-50", "bg-black", "#fff", "#3b82f6", "#3B82F6cc"] {
+            round_trips(text.parse::<Color>().unwrap(), text);
+        }
+        assert_eq!(" #fff ".parse::<Color>().unwrap().to_string(), "#fff");
+        for text in ["", "blue", "bg-", "bg-Blue-500", "bg--blue", "bg-blue-", "bg-500", "#ff", "#ffff", "#gggggg", "red;"] {
+            rejected::<Color>(text);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use rusqlite::{Result, params};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::undo::{Snapshot, Values};
+use super::{Database, Timestamp};
+
+// Field-level audit trail of every change to a task. Commands make their
+// changes through `Database::record`, which logs them; a write to tasks,
+// subtasks or tags that isn't a user operation goes through
+// `Database::with_history` instead. Row fields are logged under their column name with
+// the stored value as text; subtask and tag changes use the pseudo-fields
+// below. A new task gets a single `created` entry.
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TaskHistoryEntry {
+    pub id: i64,
+    pub task_id: String,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    // What made the change, e.g. "Edit task" or "Undo: Delete task"
+    pub source: String,
+    pub changed_at: Timestamp,
+}
+
+// Bumped on every save, so not worth an entry of its own
+const UNLOGGED_FIELDS: &[&str] = &["id", "updated_at"];
+
+type FieldChange = (String, Option<String>, Option<String>);
+
+fn as_text(value: Option<&serde_json::Value>) -> Option<String> {
+    match value? {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn texts(rows: &[Values], field: &str) -> Vec<String> {
+    rows.iter().filter_map(|row| as_text(row.get(field))).collect()
+}
+
+fn json_list(items: Vec<String>) -> Option<String> {
+    serde_json::to_string(&items).ok()
+}
+
+fn children<'a>(snapshot: &'a Snapshot, table: &str) -> &'a [Values] {
+    snapshot.children.get(table).map(Vec::as_slice).unwrap_or_default()
+}
+
+// `subtask_added` / `subtask_removed` carry the subtask's text,
+// `subtask_text` and `subtask_completed` its old and new value, and
+// `subtask_order` / `tags` the whole list before and after as JSON
+fn diff(before: &Snapshot, after: &Snapshot) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+
+    let fields: BTreeSet<&String> = before.row.keys().chain(after.row.keys()).collect();
+    for field in fields {
+        if UNLOGGED_FIELDS.contains(&field.as_str()) {
+            continue;
+        }
+        let (old, new) = (as_text(before.row.get(field)), as_text(after.row.get(field)));
+        if old != new {
+            changes.push((field.clone(), old, new));
+        }
+    }
+
+    let by_id = |rows: &[Values]| -> BTreeMap<String, Values> {
+        rows.iter()
+            .filter_map(|row| Some((as_text(row.get("id"))?, row.clone())))
+            .collect()
+    };
+    let (old_subtasks, new_subtasks) = (children(before, "subtasks"), children(after, "subtasks"));
+    let (old_by_id, new_by_id) = (by_id(old_subtasks), by_id(new_subtasks));
+    for (id, old) in &old_by_id {
+        let old_text = as_text(old.get("text"));
+        match new_by_id.get(id) {
+            None => changes.push(("subtask_removed".to_string(), old_text, None)),
+            Some(new) => {
+                let new_text = as_text(new.get("text"));
+                if old_text != new_text {
+                    changes.push(("subtask_text".to_string(), old_text, new_text));
+                }
+                let (old_done, new_done) = (as_text(old.get("completed")), as_text(new.get("completed")));
+                if old_done != new_done {
+                    changes.push(("subtask_completed".to_string(), old_done, new_done));
+                }
+            }
+        }
+    }
+    for (id, new) in &new_by_id {
+        if !old_by_id.contains_key(id) {
+            changes.push(("subtask_added".to_string(), None, as_text(new.get("text"))));
+        }
+    }
+
+    // Order of the subtasks both sides have, so adding or removing one
+    // doesn't also count as a reorder
+    let in_order = |rows: &[Values], other: &BTreeMap<String, Values>| -> Vec<Values> {
+        let mut kept: Vec<Values> = rows
+            .iter()
+            .filter(|row| as_text(row.get("id")).is_some_and(|id| other.contains_key(&id)))
+            .cloned()
+            .collect();
+        kept.sort_by_key(|row| row.get("position").and_then(serde_json::Value::as_i64));
+        kept
+    };
+    let (old_order, new_order) = (in_order(old_subtasks, &new_by_id), in_order(new_subtasks, &old_by_id));
+    if texts(&old_order, "id") != texts(&new_order, "id") {
+        changes.push((
+            "subtask_order".to_string(),
+            json_list(texts(&old_order, "text")),
+            json_list(texts(&new_order, "text")),
+        ));
+    }
+
+    let (old_tags, new_tags) = (texts(children(before, "tags"), "tag"), texts(children(after, "tags"), "tag"));
+    if old_tags != new_tags {
+        changes.push(("tags".to_string(), json_list(old_tags), json_list(new_tags)));
+    }
+
+    changes
+}
+
+impl Database {
+    // Newest first. Trashed tasks keep their history; it goes when the task
+    // is deleted for good.
+    pub fn get_task_history(&self, task_id: &str) -> Result<Vec<TaskHistoryEntry>> {
+        self.conn.query_row("SELECT 1 FROM tasks WHERE id = ?1", [task_id], |_| Ok(()))?;
+        let mut stmt = self.conn.prepare(
+            "SELECT id, task_id, field, old_value, new_value, source, changed_at
+             FROM task_history WHERE task_id = ?1 ORDER BY id DESC",
+        )?;
+        let entries = stmt.query_map([task_id], |row| {
+            Ok(TaskHistoryEntry {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                field: row.get(2)?,
+                old_value: row.get(3)?,
+                new_value: row.get(4)?,
+                source: row.get(5)?,
+                changed_at: row.get(6)?,
+            })
+        })?;
+        entries.collect()
+    }
+
+    // Logs how a task went from `before` to `after`. Nothing is logged for a
+    // task that no longer exists, since its history goes with it.
+    pub(super) fn log_task_change(
+        &self,
+        source: &str,
+        task_id: &str,
+        before: Option<&Snapshot>,
+        after: Option<&Snapshot>,
+    ) -> Result<()> {
+        let changes = match (before, after) {
+            (_, None) => return Ok(()),
+            (None, Some(after)) => vec![("created".to_string(), None, as_text(after.row.get("title")))],
+            (Some(before), Some(after)) => diff(before, after),
+        };
+        let now = Timestamp::now();
+        for (field, old_value, new_value) in changes {
+            self.conn.execute(
+                "INSERT INTO task_history (task_id, field, old_value, new_value, source, changed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![task_id, field, old_value, new_value, source, now],
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,678 @@
+use rusqlite::{Connection, Result};
+use std::fs;
+use std::path::Path;
+use chrono::Utc;
+
+// Forward-only schema migration. The schema version is tracked with
+// `PRAGMA user_version`, so migration N brings the database to version N.
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+// Ordered list of migrations. Never edit a migration that has shipped -
+// append a new one instead.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        // Uses IF NOT EXISTS so databases created before migrations existed
+        // (user_version = 0 but tables present) are adopted as-is.
+        sql: "
+            CREATE TABLE IF NOT EXISTS folders (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                color TEXT NOT NULL,
+                description TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS projects (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                color TEXT NOT NULL,
+                description TEXT,
+                folder_id INTEGER,
+                FOREIGN KEY (folder_id) REFERENCES folders (id)
+            );
+
+            CREATE TABLE IF NOT EXISTS tasks (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                description TEXT,
+                due_date TEXT,
+                priority TEXT NOT NULL DEFAULT 'medium',
+                completed BOOLEAN NOT NULL DEFAULT 0,
+                project_id INTEGER,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects (id)
+            );
+
+            CREATE TABLE IF NOT EXISTS subtasks (
+                id TEXT PRIMARY KEY,
+                task_id TEXT NOT NULL,
+                text TEXT NOT NULL,
+                completed BOOLEAN NOT NULL DEFAULT 0,
+                FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE
+            );
+
+            CREATE TABLE IF NOT EXISTS tags (
+                task_id TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                PRIMARY KEY (task_id, tag),
+                FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE
+            );
+
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_tasks_project_id ON tasks(project_id);
+            CREATE INDEX IF NOT EXISTS idx_tasks_completed ON tasks(completed);
+            CREATE INDEX IF NOT EXISTS idx_tasks_due_date ON tasks(due_date);
+            CREATE INDEX IF NOT EXISTS idx_subtasks_task_id ON subtasks(task_id);
+            CREATE INDEX IF NOT EXISTS idx_tags_task_id ON tags(task_id);
+        ",
+    },
+    Migration {
+        version: 2,
+        name: "clean_orphaned_rows",
+        // Foreign keys were never enforced before this version, so drop or
+        // detach rows that point at records which no longer exist.
+        sql: "
+            DELETE FROM subtasks WHERE task_id NOT IN (SELECT id FROM tasks);
+            DELETE FROM tags WHERE task_id NOT IN (SELECT id FROM tasks);
+            UPDATE tasks SET project_id = NULL
+                WHERE project_id IS NOT NULL AND project_id NOT IN (SELECT id FROM projects);
+            UPDATE projects SET folder_id = NULL
+                WHERE folder_id IS NOT NULL AND folder_id NOT IN (SELECT id FROM folders);
+        ",
+    },
+    Migration {
+        version: 3,
+        name: "add_task_status",
+        sql: "
+            ALTER TABLE tasks ADD COLUMN status TEXT NOT NULL DEFAULT 'todo';
+            UPDATE tasks SET status = 'done' WHERE completed = 1;
+            CREATE INDEX IF NOT EXISTS idx_tasks_status ON tasks(status);
+        ",
+    },
+    Migration {
+        version: 4,
+        name: "add_workflow_columns",
+        sql: "
+            CREATE TABLE workflow_columns (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                status TEXT NOT NULL DEFAULT 'todo',
+                wip_limit INTEGER,
+                FOREIGN KEY (project_id) REFERENCES projects (id) ON DELETE CASCADE
+            );
+
+            ALTER TABLE tasks ADD COLUMN column_id INTEGER
+                REFERENCES workflow_columns (id) ON DELETE SET NULL;
+
+            CREATE INDEX idx_workflow_columns_project_id ON workflow_columns(project_id);
+            CREATE INDEX idx_tasks_column_id ON tasks(column_id);
+        ",
+    },
+    Migration {
+        version: 5,
+        name: "add_recurrence",
+        // Completions reference the series rather than the task so history
+        // outlives deleted occurrences.
+        sql: "
+            ALTER TABLE tasks ADD COLUMN recurrence TEXT;
+            ALTER TABLE tasks ADD COLUMN series_id TEXT;
+            ALTER TABLE tasks ADD COLUMN occurrence INTEGER NOT NULL DEFAULT 1;
+
+            CREATE TABLE task_completions (
+                task_id TEXT PRIMARY KEY,
+                series_id TEXT NOT NULL,
+                occurrence INTEGER NOT NULL,
+                due_date TEXT,
+                completed_at TEXT NOT NULL
+            );
+
+            CREATE INDEX idx_tasks_series_id ON tasks(series_id);
+            CREATE INDEX idx_task_completions_series_id ON task_completions(series_id);
+        ",
+    },
+    Migration {
+        version: 6,
+        name: "add_reminders",
+        sql: "
+            CREATE TABLE reminders (
+                id TEXT PRIMARY KEY,
+                task_id TEXT NOT NULL,
+                remind_at TEXT,
+                offset_minutes INTEGER,
+                fire_at TEXT,
+                status TEXT NOT NULL DEFAULT 'pending',
+                fired_at TEXT,
+                FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE,
+                CHECK ((remind_at IS NULL) != (offset_minutes IS NULL))
+            );
+
+            CREATE INDEX idx_reminders_task_id ON reminders(task_id);
+            CREATE INDEX idx_reminders_pending ON reminders(status, fire_at);
+        ",
+    },
+    Migration {
+        version: 7,
+        name: "add_task_search",
+        // Full-text index keyed by the task's rowid. Subtask text and tags are
+        // flattened into one column each and rebuilt by the triggers below.
+        // The main database is never VACUUMed in place (backups use VACUUM
+        // INTO), so task rowids stay stable.
+        sql: "
+            CREATE VIRTUAL TABLE task_search USING fts5(
+                title, description, subtasks, tags,
+                tokenize = 'unicode61 remove_diacritics 2'
+            );
+
+            INSERT INTO task_search (rowid, title, description, subtasks, tags)
+                SELECT t.rowid, t.title, t.description,
+                    (SELECT group_concat(text, ' ') FROM subtasks WHERE task_id = t.id),
+                    (SELECT group_concat(tag, ' ') FROM tags WHERE task_id = t.id)
+                FROM tasks t;
+
+            CREATE TRIGGER task_search_tasks_insert AFTER INSERT ON tasks BEGIN
+                INSERT INTO task_search (rowid, title, description, subtasks, tags) VALUES (
+                    new.rowid, new.title, new.description,
+                    (SELECT group_concat(text, ' ') FROM subtasks WHERE task_id = new.id),
+                    (SELECT group_concat(tag, ' ') FROM tags WHERE task_id = new.id)
+                );
+            END;
+
+            CREATE TRIGGER task_search_tasks_update AFTER UPDATE OF title, description ON tasks BEGIN
+                UPDATE task_search SET title = new.title, description = new.description
+                    WHERE rowid = new.rowid;
+            END;
+
+            CREATE TRIGGER task_search_tasks_delete AFTER DELETE ON tasks BEGIN
+                DELETE FROM task_search WHERE rowid = old.rowid;
+            END;
+
+            CREATE TRIGGER task_search_subtasks_insert AFTER INSERT ON subtasks BEGIN
+                UPDATE task_search
+                    SET subtasks = (SELECT group_concat(text, ' ') FROM subtasks WHERE task_id = new.task_id)
+                    WHERE rowid = (SELECT rowid FROM tasks WHERE id = new.task_id);
+            END;
+
+            CREATE TRIGGER task_search_subtasks_update AFTER UPDATE OF text ON subtasks BEGIN
+                UPDATE task_search
+                    SET subtasks = (SELECT group_concat(text, ' ') FROM subtasks WHERE task_id = new.task_id)
+                    WHERE rowid = (SELECT rowid FROM tasks WHERE id = new.task_id);
+            END;
+
+            CREATE TRIGGER task_search_subtasks_delete AFTER DELETE ON subtasks BEGIN
+                UPDATE task_search
+                    SET subtasks = (SELECT group_concat(text, ' ') FROM subtasks WHERE task_id = old.task_id)
+                    WHERE rowid = (SELECT rowid FROM tasks WHERE id = old.task_id);
+            END;
+
+            CREATE TRIGGER task_search_tags_insert AFTER INSERT ON tags BEGIN
+                UPDATE task_search
+                    SET tags = (SELECT group_concat(tag, ' ') FROM tags WHERE task_id = new.task_id)
+                    WHERE rowid = (SELECT rowid FROM tasks WHERE id = new.task_id);
+            END;
+
+            CREATE TRIGGER task_search_tags_delete AFTER DELETE ON tags BEGIN
+                UPDATE task_search
+                    SET tags = (SELECT group_concat(tag, ' ') FROM tags WHERE task_id = old.task_id)
+                    WHERE rowid = (SELECT rowid FROM tasks WHERE id = old.task_id);
+            END;
+        ",
+    },
+    Migration {
+        version: 8,
+        name: "add_saved_searches",
+        // `tags` holds a JSON array. A search scoped to a project goes away
+        // with the project rather than silently widening to all tasks.
+        sql: "
+            CREATE TABLE saved_searches (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                tags TEXT NOT NULL DEFAULT '[]',
+                priority TEXT,
+                project_id INTEGER,
+                filter_by TEXT NOT NULL DEFAULT 'all',
+                completed BOOLEAN,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects (id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX idx_saved_searches_project_id ON saved_searches(project_id);
+        ",
+    },
+    Migration {
+        version: 9,
+        name: "add_templates",
+        // `subtasks` and `tags` hold JSON arrays
+        sql: "
+            CREATE TABLE templates (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                description TEXT,
+                title TEXT NOT NULL,
+                task_description TEXT NOT NULL DEFAULT '',
+                priority TEXT NOT NULL DEFAULT 'medium',
+                subtasks TEXT NOT NULL DEFAULT '[]',
+                tags TEXT NOT NULL DEFAULT '[]',
+                project_id INTEGER,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects (id) ON DELETE SET NULL
+            );
+        ",
+    },
+    Migration {
+        version: 10,
+        name: "add_project_templates",
+        // `tasks` holds a JSON array of tasks with due dates stored as day
+        // offsets from the anchor date chosen at instantiation
+        sql: "
+            CREATE TABLE project_templates (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                color TEXT NOT NULL,
+                description TEXT,
+                tasks TEXT NOT NULL DEFAULT '[]',
+                created_at TEXT NOT NULL
+            );
+        ",
+    },
+    Migration {
+        version: 11,
+        name: "add_task_sort_indexes",
+        // Keyset pagination in `list_tasks` orders by these with `id` as the
+        // tie-breaker
+        sql: "
+            CREATE INDEX idx_tasks_created_at ON tasks(created_at, id);
+            CREATE INDEX idx_tasks_updated_at ON tasks(updated_at, id);
+        ",
+    },
+    Migration {
+        version: 12,
+        name: "normalize_task_fields",
+        // Priority, due date and color are now parsed when read, so values the
+        // old string fields accepted are rewritten into the validated forms:
+        // unknown priorities become medium, due dates that are neither a plain
+        // date nor an RFC 3339 timestamp keep only their date (or are dropped),
+        // and unrecognised colors fall back to the default blue.
+        sql: "
+            UPDATE tasks SET priority = CASE
+                WHEN lower(trim(priority)) IN ('low', 'medium', 'high') THEN lower(trim(priority))
+                ELSE 'medium'
+            END;
+            UPDATE templates SET priority = CASE
+                WHEN lower(trim(priority)) IN ('low', 'medium', 'high') THEN lower(trim(priority))
+                ELSE 'medium'
+            END;
+            UPDATE project_templates SET tasks = (
+                SELECT json_group_array(json_set(value, '$.priority', CASE
+                    WHEN lower(trim(json_extract(value, '$.priority'))) IN ('low', 'medium', 'high')
+                        THEN lower(trim(json_extract(value, '$.priority')))
+                    ELSE 'medium'
+                END))
+                FROM json_each(project_templates.tasks)
+            )
+            WHERE json_valid(tasks) AND json_type(tasks) = 'array';
+
+            UPDATE tasks SET due_date = NULLIF(trim(due_date), '') WHERE due_date IS NOT NULL;
+            UPDATE tasks SET due_date = CASE
+                WHEN date(substr(due_date, 1, 10)) = substr(due_date, 1, 10) THEN substr(due_date, 1, 10)
+                ELSE NULL
+            END
+            WHERE due_date IS NOT NULL
+                AND NOT (length(due_date) = 10 AND date(due_date) = due_date)
+                AND NOT (
+                    due_date GLOB '????-??-??T??:??:??*'
+                    AND (due_date GLOB '*Z' OR due_date GLOB '*[+-][0-9][0-9]:[0-9][0-9]')
+                    AND datetime(substr(due_date, 1, 19)) IS NOT NULL
+                    AND date(substr(due_date, 1, 10)) = substr(due_date, 1, 10)
+                );
+
+            UPDATE projects SET color = trim(color);
+            UPDATE projects SET color = 'bg-blue-500'
+            WHERE NOT (
+                color GLOB 'bg-[a-z]*' AND NOT substr(color, 4) GLOB '*[^a-z0-9-]*'
+                AND NOT color GLOB '*-' AND NOT color GLOB '*--*'
+            ) AND NOT (
+                color GLOB '#*' AND length(color) IN (4, 7, 9)
+                AND NOT substr(color, 2) GLOB '*[^0-9a-fA-F]*'
+            );
+            UPDATE folders SET color = trim(color);
+            UPDATE folders SET color = 'bg-blue-500'
+            WHERE NOT (
+                color GLOB 'bg-[a-z]*' AND NOT substr(color, 4) GLOB '*[^a-z0-9-]*'
+                AND NOT color GLOB '*-' AND NOT color GLOB '*--*'
+            ) AND NOT (
+                color GLOB '#*' AND length(color) IN (4, 7, 9)
+                AND NOT substr(color, 2) GLOB '*[^0-9a-fA-F]*'
+            );
+            UPDATE project_templates SET color = trim(color);
+            UPDATE project_templates SET color = 'bg-blue-500'
+            WHERE NOT (
+                color GLOB 'bg-[a-z]*' AND NOT substr(color, 4) GLOB '*[^a-z0-9-]*'
+                AND NOT color GLOB '*-' AND NOT color GLOB '*--*'
+            ) AND NOT (
+                color GLOB '#*' AND length(color) IN (4, 7, 9)
+                AND NOT substr(color, 2) GLOB '*[^0-9a-fA-F]*'
+            );
+        ",
+    },
+    Migration {
+        version: 13,
+        name: "add_subtask_positions",
+        // Existing subtasks keep the insertion order they were listed in
+        sql: "
+            ALTER TABLE subtasks ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+            UPDATE subtasks SET position = (
+                SELECT count(*) FROM subtasks AS earlier
+                WHERE earlier.task_id = subtasks.task_id AND earlier.rowid < subtasks.rowid
+            );
+
+            DROP INDEX IF EXISTS idx_subtasks_task_id;
+            CREATE INDEX idx_subtasks_task_position ON subtasks(task_id, position);
+        ",
+    },
+    Migration {
+        version: 14,
+        name: "add_trash",
+        // Deleted rows keep their data and get a `deleted_at` time; every
+        // query over these tables must filter on `deleted_at IS NULL`
+        sql: "
+            ALTER TABLE tasks ADD COLUMN deleted_at TEXT;
+            ALTER TABLE projects ADD COLUMN deleted_at TEXT;
+            ALTER TABLE folders ADD COLUMN deleted_at TEXT;
+
+            CREATE INDEX idx_tasks_deleted_at ON tasks(deleted_at);
+            CREATE INDEX idx_projects_deleted_at ON projects(deleted_at);
+            CREATE INDEX idx_folders_deleted_at ON folders(deleted_at);
+        ",
+    },
+    Migration {
+        version: 15,
+        name: "add_operations",
+        // Undo history; `changes` holds JSON row snapshots (see undo.rs)
+        sql: "
+            CREATE TABLE operations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                label TEXT NOT NULL,
+                changes TEXT NOT NULL,
+                undone BOOLEAN NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL
+            );
+        ",
+    },
+    Migration {
+        version: 16,
+        name: "add_task_history",
+        sql: "
+            CREATE TABLE task_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                task_id TEXT NOT NULL,
+                field TEXT NOT NULL,
+                old_value TEXT,
+                new_value TEXT,
+                source TEXT NOT NULL,
+                changed_at TEXT NOT NULL,
+                FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX idx_task_history_task_id ON task_history(task_id);
+        ",
+    },
+    Migration {
+        version: 17,
+        name: "add_completed_at",
+        // Tasks completed before this have no record of when; the series
+        // history or, failing that, the last update is the best guess
+        sql: "
+            ALTER TABLE tasks ADD COLUMN completed_at TEXT;
+
+            UPDATE tasks SET completed_at = COALESCE(
+                (SELECT completed_at FROM task_completions WHERE task_id = tasks.id),
+                updated_at
+            )
+            WHERE completed = 1;
+
+            CREATE INDEX idx_tasks_completed_at ON tasks(completed_at);
+        ",
+    },
+    Migration {
+        version: 18,
+        name: "add_pomodoro_sessions",
+        // Sessions outlive their task so focus totals don't shrink when
+        // the trash is emptied
+        sql: "
+            CREATE TABLE pomodoro_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                task_id TEXT REFERENCES tasks(id) ON DELETE SET NULL,
+                phase TEXT NOT NULL,
+                planned_seconds INTEGER NOT NULL,
+                actual_seconds INTEGER,
+                interrupted BOOLEAN NOT NULL DEFAULT 0,
+                started_at TEXT NOT NULL,
+                ended_at TEXT
+            );
+
+            CREATE INDEX idx_pomodoro_sessions_started_at ON pomodoro_sessions(started_at);
+            CREATE INDEX idx_pomodoro_sessions_task_id ON pomodoro_sessions(task_id);
+        ",
+    },
+    Migration {
+        version: 19,
+        name: "clear_invalid_recurrence_until",
+        // `until` is read as a date now; one that never parsed was ignored
+        // before, so dropping it keeps the series running as it did
+        sql: "
+            UPDATE tasks SET recurrence = json_set(recurrence, '$.until', NULL)
+            WHERE recurrence IS NOT NULL
+              AND json_extract(recurrence, '$.until') IS NOT NULL
+              AND date(json_extract(recurrence, '$.until')) IS NOT json_extract(recurrence, '$.until');
+        ",
+    },
+    Migration {
+        version: 20,
+        name: "normalize_saved_search_priority",
+        // Saved search priorities are parsed when read, like task priorities
+        // since migration 12. An unknown one never matched a task; it is
+        // dropped rather than guessed at.
+        sql: "
+            UPDATE saved_searches SET priority = CASE
+                WHEN lower(trim(priority)) IN ('low', 'medium', 'high') THEN lower(trim(priority))
+                ELSE NULL
+            END
+            WHERE priority IS NOT NULL;
+        ",
+    },
+];
+
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+pub fn current_version(conn: &Connection) -> Result<u32> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+// Applies every pending migration. When `backup_dir` is given and the
+// database already holds data, a copy is written there first.
+pub fn run(conn: &mut Connection, backup_dir: Option<&Path>) -> Result<()> {
+    let current = current_version(conn)?;
+    let latest = latest_version();
+
+    if current > latest {
+        return Err(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
+            Some(format!(
+                "Database schema version {} is newer than this build supports ({})",
+                current, latest
+            )),
+        ));
+    }
+
+    if current == latest {
+        return Ok(());
+    }
+
+    if let Some(dir) = backup_dir
+        && has_user_tables(conn)?
+    {
+        backup(conn, dir, current)?;
+    }
+
+    // Foreign keys are switched off while migrating so that table rebuilds
+    // don't fire cascades; integrity is verified before committing instead.
+    // This pragma is a no-op inside a transaction, so it is set around it.
+    conn.pragma_update(None, "foreign_keys", false)?;
+    let result = apply_pending(conn, current);
+    conn.pragma_update(None, "foreign_keys", true)?;
+    result
+}
+
+fn apply_pending(conn: &mut Connection, current: u32) -> Result<()> {
+    let tx = conn.transaction()?;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        log::info!("Applying migration {}: {}", migration.version, migration.name);
+        tx.execute_batch(migration.sql)?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+    }
+
+    let violations: i64 = tx.query_row(
+        "SELECT COUNT(*) FROM pragma_foreign_key_check",
+        [],
+        |row| row.get(0),
+    )?;
+    if violations > 0 {
+        return Err(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+            Some(format!("Migration left {} foreign key violations", violations)),
+        ));
+    }
+
+    tx.commit()
+}
+
+fn has_user_tables(conn: &Connection) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn backup(conn: &Connection, dir: &Path, version: u32) -> Result<()> {
+    fs::create_dir_all(dir).map_err(|e| {
+        rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CANTOPEN),
+            Some(format!("Failed to create backup directory: {}", e)),
+        )
+    })?;
+
+    let file_name = format!(
+        "ultralist-v{}-{}.db",
+        version,
+        Utc::now().format("%Y%m%d%H%M%S")
+    );
+    let backup_path = dir.join(file_name);
+    log::info!("Backing up database to {}", backup_path.display());
+
+    conn.execute(
+        "VACUUM INTO ?1",
+        [backup_path.to_string_lossy().to_string()],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(conn: &Connection, sql: &str) -> i64 {
+        conn.query_row(sql, [], |row| row.get(0)).unwrap()
+    }
+
+    // The schema and data an install from before migrations existed left
+    // behind: the tables of the first migration, `user_version` still 0
+    fn v0_database() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0].sql).unwrap();
+        conn.execute_batch(
+            "INSERT INTO folders (id, name, color, description) VALUES (1, 'Home', '#10b981', NULL);
+             INSERT INTO projects (id, name, color, description, folder_id) VALUES (1, 'Chores', '#3b82f6', NULL, 1);
+             INSERT INTO tasks (id, title, description, due_date, priority, completed, project_id, created_at, updated_at)
+                 VALUES ('t1', 'Buy groceries', 'Milk and eggs', '2026-10-01', 'high', 0, 1,
+                         '2026-09-01T08:00:00.000Z', '2026-09-01T08:00:00.000Z');
+             INSERT INTO subtasks (id, task_id, text, completed) VALUES ('s1', 't1', 'Check the fridge', 1);
+             INSERT INTO tags (task_id, tag) VALUES ('t1', 'errands');
+             INSERT INTO settings (key, value) VALUES ('theme', 'dark');",
+        )
+        .unwrap();
+        assert_eq!(current_version(&conn).unwrap(), 0);
+        conn
+    }
+
+    fn matches(conn: &Connection, query: &str) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM task_search WHERE task_search MATCH ?1", [query], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn upgrades_a_v0_database_to_the_latest_version() {
+        let mut conn = v0_database();
+        let backups = std::env::temp_dir().join(format!("ultralist-migrations-{}", uuid::Uuid::new_v4()));
+
+        run(&mut conn, Some(&backups)).unwrap();
+
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM pragma_foreign_key_check"), 0);
+        assert_eq!(count(&conn, "PRAGMA foreign_keys"), 1);
+        assert_eq!(fs::read_dir(&backups).unwrap().count(), 1);
+        fs::remove_dir_all(&backups).unwrap();
+
+        // Existing rows survive every table rebuild
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM tasks WHERE id = 't1' AND project_id = 1"), 1);
+        assert_eq!(count(&conn, "SELECT completed FROM subtasks WHERE id = 's1'"), 1);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM projects WHERE id = 1 AND folder_id = 1"), 1);
+
+        // Existing tasks are indexed, and the triggers keep the index current
+        assert_eq!(matches(&conn, "groceries"), 1);
+        assert_eq!(matches(&conn, "fridge"), 1);
+        assert_eq!(matches(&conn, "errands"), 1);
+        conn.execute_batch(
+            "UPDATE tasks SET title = 'Buy vegetables' WHERE id = 't1';
+             UPDATE subtasks SET text = 'Check the pantry' WHERE id = 's1';
+             DELETE FROM tags WHERE task_id = 't1';
+             INSERT INTO tags (task_id, tag) VALUES ('t1', 'market');",
+        )
+        .unwrap();
+        assert_eq!(matches(&conn, "groceries"), 0);
+        assert_eq!(matches(&conn, "vegetables"), 1);
+        assert_eq!(matches(&conn, "fridge"), 0);
+        assert_eq!(matches(&conn, "pantry"), 1);
+        assert_eq!(matches(&conn, "errands"), 0);
+        assert_eq!(matches(&conn, "market"), 1);
+        conn.execute("DELETE FROM tasks WHERE id = 't1'", []).unwrap();
+        assert_eq!(matches(&conn, "vegetables"), 0);
+
+        // Running again is a no-op
+        run(&mut conn, None).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+    }
+
+    #[test]
+    fn refuses_a_database_from_a_newer_build() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", latest_version() + 1).unwrap();
+
+        assert!(run(&mut conn, None).is_err());
+    }
+}
@@ -0,0 +1,344 @@
+use rusqlite::{OptionalExtension, Result, Row, params};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+use chrono::{Local, NaiveDate};
+use std::collections::{BTreeMap, HashMap};
+
+use super::stats::{date_range, day_end, day_start};
+use super::{Database, Timestamp};
+use crate::error::AppError;
+
+// Settings key holding `PomodoroSettings` as JSON
+pub const POMODORO_SETTINGS: &str = "pomodoro_settings";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PomodoroPhase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl PomodoroPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PomodoroPhase::Work => "work",
+            PomodoroPhase::ShortBreak => "short-break",
+            PomodoroPhase::LongBreak => "long-break",
+        }
+    }
+}
+
+impl ToSql for PomodoroPhase {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for PomodoroPhase {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "work" => Ok(PomodoroPhase::Work),
+            "short-break" => Ok(PomodoroPhase::ShortBreak),
+            "long-break" => Ok(PomodoroPhase::LongBreak),
+            other => Err(FromSqlError::Other(format!("Unknown pomodoro phase: {}", other).into())),
+        }
+    }
+}
+
+// Durations are in minutes
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PomodoroSettings {
+    pub work_duration: u32,
+    pub short_break_duration: u32,
+    pub long_break_duration: u32,
+    pub sessions_until_long_break: u32,
+}
+
+impl Default for PomodoroSettings {
+    fn default() -> Self {
+        PomodoroSettings {
+            work_duration: 25,
+            short_break_duration: 5,
+            long_break_duration: 15,
+            sessions_until_long_break: 4,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PomodoroSession {
+    pub id: i64,
+    // `None` for a session without a task, or once its task is purged
+    pub task_id: Option<String>,
+    pub phase: PomodoroPhase,
+    pub planned_seconds: i64,
+    // `None` while the session is running
+    pub actual_seconds: Option<i64>,
+    // Aborted before the planned time was up
+    pub interrupted: bool,
+    pub started_at: Timestamp,
+    pub ended_at: Option<Timestamp>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FocusStatsRequest {
+    // Inclusive `YYYY-MM-DD` range; the last 30 days by default
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct FocusTotal {
+    pub focus_minutes: f64,
+    // Work sessions that ran to the end
+    pub completed_sessions: i64,
+    pub interrupted_sessions: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FocusBreakdown {
+    // Task id, project id as text, or the day (`YYYY-MM-DD`); `None` for
+    // sessions without a task or project
+    pub key: Option<String>,
+    pub name: String,
+    #[serde(flatten)]
+    pub total: FocusTotal,
+}
+
+// Work sessions only; break time isn't focus time
+#[derive(Debug, Serialize, Clone)]
+pub struct FocusStats {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    #[serde(flatten)]
+    pub total: FocusTotal,
+    // Every day in the range, oldest first
+    pub per_day: Vec<FocusBreakdown>,
+    // Most focus first
+    pub per_task: Vec<FocusBreakdown>,
+    pub per_project: Vec<FocusBreakdown>,
+}
+
+const SESSION_COLUMNS: &str =
+    "id, task_id, phase, planned_seconds, actual_seconds, interrupted, started_at, ended_at";
+
+fn session_from_row(row: &Row) -> Result<PomodoroSession> {
+    Ok(PomodoroSession {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        phase: row.get(2)?,
+        planned_seconds: row.get(3)?,
+        actual_seconds: row.get(4)?,
+        interrupted: row.get(5)?,
+        started_at: row.get(6)?,
+        ended_at: row.get(7)?,
+    })
+}
+
+impl FocusTotal {
+    fn add(&mut self, seconds: i64, interrupted: bool) {
+        self.focus_minutes += seconds as f64 / 60.0;
+        if interrupted {
+            self.interrupted_sessions += 1;
+        } else {
+            self.completed_sessions += 1;
+        }
+    }
+}
+
+fn ranked(totals: HashMap<Option<String>, (String, FocusTotal)>) -> Vec<FocusBreakdown> {
+    let mut rows: Vec<FocusBreakdown> = totals
+        .into_iter()
+        .map(|(key, (name, total))| FocusBreakdown { key, name, total })
+        .collect();
+    rows.sort_by(|a, b| {
+        b.total.focus_minutes.total_cmp(&a.total.focus_minutes).then_with(|| a.name.cmp(&b.name))
+    });
+    rows
+}
+
+impl Database {
+    pub fn get_pomodoro_settings(&self) -> Result<PomodoroSettings> {
+        let saved = self.get_setting(POMODORO_SETTINGS)?;
+        Ok(match saved.as_deref().map(serde_json::from_str) {
+            Some(Ok(settings)) => settings,
+            Some(Err(e)) => {
+                log::warn!("Ignoring saved pomodoro settings: {}", e);
+                PomodoroSettings::default()
+            }
+            None => PomodoroSettings::default(),
+        })
+    }
+
+    pub fn save_pomodoro_settings(&self, settings: &PomodoroSettings) -> Result<()> {
+        let json = serde_json::to_string(settings)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.save_setting(POMODORO_SETTINGS, &json)
+    }
+
+    // Starts timing a session. Only one session runs at a time, so one left
+    // running (e.g. by closing the app mid-session) is ended as interrupted.
+    pub fn start_pomodoro_session(
+        &self,
+        task_id: Option<&str>,
+        phase: PomodoroPhase,
+        planned_seconds: i64,
+    ) -> std::result::Result<PomodoroSession, AppError> {
+        self.transaction(|db| {
+            if let Some(task_id) = task_id {
+                db.conn.query_row(
+                    "SELECT 1 FROM tasks WHERE id = ?1 AND deleted_at IS NULL",
+                    [task_id],
+                    |_| Ok(()),
+                )?;
+            }
+            if let Some(running) = db.get_active_pomodoro_session()? {
+                db.end_pomodoro_session(running.id, None, true)?;
+            }
+            db.conn.execute(
+                "INSERT INTO pomodoro_sessions (task_id, phase, planned_seconds, started_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![task_id, phase, planned_seconds, Timestamp::now()],
+            )?;
+            Ok(db.get_pomodoro_session(db.conn.last_insert_rowid())?)
+        })
+    }
+
+    pub fn finish_pomodoro_session(
+        &self,
+        id: i64,
+        actual_seconds: Option<i64>,
+    ) -> std::result::Result<PomodoroSession, AppError> {
+        self.end_pomodoro_session(id, actual_seconds, false)
+    }
+
+    pub fn abort_pomodoro_session(
+        &self,
+        id: i64,
+        actual_seconds: Option<i64>,
+    ) -> std::result::Result<PomodoroSession, AppError> {
+        self.end_pomodoro_session(id, actual_seconds, true)
+    }
+
+    pub fn get_pomodoro_session(&self, id: i64) -> Result<PomodoroSession> {
+        self.conn.query_row(
+            &format!("SELECT {} FROM pomodoro_sessions WHERE id = ?1", SESSION_COLUMNS),
+            [id],
+            session_from_row,
+        )
+    }
+
+    pub fn get_active_pomodoro_session(&self) -> Result<Option<PomodoroSession>> {
+        self.conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM pomodoro_sessions WHERE ended_at IS NULL ORDER BY id DESC LIMIT 1",
+                    SESSION_COLUMNS
+                ),
+                [],
+                session_from_row,
+            )
+            .optional()
+    }
+
+    // `actual_seconds` is the time actually spent, which a paused timer
+    // knows better than the clock; it defaults to the time since the start
+    fn end_pomodoro_session(
+        &self,
+        id: i64,
+        actual_seconds: Option<i64>,
+        interrupted: bool,
+    ) -> std::result::Result<PomodoroSession, AppError> {
+        let session = self.get_pomodoro_session(id)?;
+        if session.ended_at.is_some() {
+            return Err(AppError::conflict(format!("Pomodoro session {} has already ended", id)));
+        }
+        if actual_seconds.is_some_and(|seconds| seconds < 0) {
+            return Err(AppError::invalid_field("actual_seconds", "actual_seconds must not be negative"));
+        }
+        let ended_at = Timestamp::now();
+        let elapsed = (ended_at.instant() - session.started_at.instant()).num_seconds().max(0);
+        self.conn.execute(
+            "UPDATE pomodoro_sessions SET actual_seconds = ?1, interrupted = ?2, ended_at = ?3 WHERE id = ?4",
+            params![actual_seconds.unwrap_or(elapsed), interrupted, ended_at, id],
+        )?;
+        Ok(self.get_pomodoro_session(id)?)
+    }
+
+    // Work sessions that have ended, bucketed by the local day they started
+    pub fn get_focus_stats(&self, request: &FocusStatsRequest) -> std::result::Result<FocusStats, AppError> {
+        let (from, to) = date_range(request.from.as_deref(), request.to.as_deref())?;
+        let (start, end) = (day_start(from), day_end(to)?);
+
+        let mut stmt = self.conn.prepare(
+            "SELECT s.started_at, s.actual_seconds, s.interrupted, s.task_id, t.title, p.id, p.name
+             FROM pomodoro_sessions s
+             LEFT JOIN tasks t ON t.id = s.task_id
+             LEFT JOIN projects p ON p.id = t.project_id
+             WHERE s.phase = ?1 AND s.ended_at IS NOT NULL
+               AND s.started_at >= ?2 AND s.started_at < ?3",
+        )?;
+        let sessions = stmt.query_map(params![PomodoroPhase::Work, start, end], |row| {
+            let started_at: Timestamp = row.get(0)?;
+            let task: Option<String> = row.get(3)?;
+            let project: Option<i32> = row.get(5)?;
+            Ok((
+                started_at.instant().with_timezone(&Local).date_naive(),
+                row.get::<_, i64>(1)?,
+                row.get::<_, bool>(2)?,
+                task.map(|id| row.get::<_, String>(4).map(|title| (id, title))).transpose()?,
+                project.map(|id| row.get::<_, String>(6).map(|name| (id, name))).transpose()?,
+            ))
+        })?;
+
+        let mut total = FocusTotal::default();
+        let mut per_day: BTreeMap<NaiveDate, FocusTotal> = BTreeMap::new();
+        for day in from.iter_days().take_while(|day| *day <= to) {
+            per_day.insert(day, FocusTotal::default());
+        }
+        let mut per_task = HashMap::new();
+        let mut per_project = HashMap::new();
+
+        for session in sessions {
+            let (day, seconds, interrupted, task, project) = session?;
+            total.add(seconds, interrupted);
+            per_day.entry(day).or_default().add(seconds, interrupted);
+
+            let (task_key, task_name) = match task {
+                Some((id, title)) => (Some(id), title),
+                None => (None, "No task".to_string()),
+            };
+            per_task
+                .entry(task_key)
+                .or_insert_with(|| (task_name, FocusTotal::default()))
+                .1
+                .add(seconds, interrupted);
+
+            let (project_key, project_name) = match project {
+                Some((id, name)) => (Some(id.to_string()), name),
+                None => (None, "Inbox".to_string()),
+            };
+            per_project
+                .entry(project_key)
+                .or_insert_with(|| (project_name, FocusTotal::default()))
+                .1
+                .add(seconds, interrupted);
+        }
+
+        Ok(FocusStats {
+            from,
+            to,
+            total,
+            per_day: per_day
+                .into_iter()
+                .map(|(day, total)| {
+                    let day = day.to_string();
+                    FocusBreakdown { key: Some(day.clone()), name: day, total }
+                })
+                .collect(),
+            per_task: ranked(per_task),
+            per_project: ranked(per_project),
+        })
+    }
+}
@@ -0,0 +1,167 @@
+use rusqlite::{Connection, OpenFlags, Result};
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, TryLockError};
+use std::time::Duration;
+
+use super::Database;
+
+// Read-only connections kept open next to the writer
+const READERS: usize = 4;
+// How long a connection waits for a lock held by another connection
+pub(super) const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub enum PoolError {
+    // The blocking task panicked or was cancelled
+    Worker(String),
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoolError::Worker(message) => write!(f, "Database worker failed: {}", message),
+        }
+    }
+}
+
+fn worker_failed(e: impl fmt::Display) -> PoolError {
+    log::error!("Database worker failed: {}", e);
+    PoolError::Worker(e.to_string())
+}
+
+struct Readers {
+    connections: Vec<Mutex<Database>>,
+    next: AtomicUsize,
+}
+
+impl Readers {
+    // Takes an idle connection if there is one, otherwise queues on the next
+    // one in turn. A read-only connection has no state a panicking reader
+    // could have left half-changed, so poisoned locks are simply reused.
+    fn with<T>(&self, f: impl FnOnce(&Database) -> T) -> T {
+        for connection in &self.connections {
+            match connection.try_lock() {
+                Ok(db) => return f(&db),
+                Err(TryLockError::Poisoned(e)) => return f(&e.into_inner()),
+                Err(TryLockError::WouldBlock) => continue,
+            }
+        }
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.connections.len();
+        let db = self.connections[index].lock().unwrap_or_else(PoisonError::into_inner);
+        f(&db)
+    }
+}
+
+// Shared database handle. All SQLite work runs on Tauri's blocking thread
+// pool so a slow query never stalls the async runtime. Writes are serialized
+// on a single connection; reads use a handful of read-only connections and,
+// thanks to WAL mode, run in parallel with each other and with a write.
+pub struct DatabasePool {
+    writer: Arc<Mutex<Database>>,
+    readers: Arc<Readers>,
+}
+
+impl DatabasePool {
+    // Opens (and migrates) the writer first so readers always see the
+    // current schema
+    pub fn open() -> Result<Self> {
+        let writer = Database::new()?;
+        let path = Database::database_path()?;
+        let connections = (0..READERS)
+            .map(|_| Database::open_read_only(&path).map(Mutex::new))
+            .collect::<Result<Vec<_>>>()?;
+        log::debug!("Opened {} read-only connections", READERS);
+
+        Ok(DatabasePool {
+            writer: Arc::new(Mutex::new(writer)),
+            readers: Arc::new(Readers { connections, next: AtomicUsize::new(0) }),
+        })
+    }
+
+    // For queries only; writing through a reader fails with "attempt to
+    // write a readonly database".
+    pub async fn read<T, E, F>(&self, f: F) -> std::result::Result<T, E>
+    where
+        F: FnOnce(&Database) -> std::result::Result<T, E> + Send + 'static,
+        T: Send + 'static,
+        E: From<PoolError> + Send + 'static,
+    {
+        let readers = self.readers.clone();
+        tauri::async_runtime::spawn_blocking(move || readers.with(f))
+            .await
+            .map_err(worker_failed)?
+    }
+
+    // A panic in `f` poisons the writer; the next write rolls back whatever
+    // transaction it left open and carries on with the connection.
+    pub async fn write<T, E, F>(&self, f: F) -> std::result::Result<T, E>
+    where
+        F: FnOnce(&Database) -> std::result::Result<T, E> + Send + 'static,
+        T: Send + 'static,
+        E: From<PoolError> + Send + 'static,
+    {
+        let writer = self.writer.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let db = writer.lock().unwrap_or_else(|e| {
+                log::warn!("Recovering the database writer after a panic");
+                let db = e.into_inner();
+                if !db.conn.is_autocommit()
+                    && let Err(e) = db.conn.execute_batch("ROLLBACK")
+                {
+                    log::error!("Failed to roll back after a panic: {}", e);
+                }
+                writer.clear_poison();
+                db
+            });
+            f(&db)
+        })
+        .await
+        .map_err(worker_failed)?
+    }
+}
+
+impl Database {
+    fn open_read_only(path: &Path) -> Result<Self> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX | OpenFlags::SQLITE_OPEN_URI,
+        )?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        Ok(Database { conn })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+    use tauri::async_runtime::block_on;
+
+    fn pool() -> DatabasePool {
+        DatabasePool {
+            writer: Arc::new(Mutex::new(Database::open_in_memory().unwrap())),
+            readers: Arc::new(Readers { connections: Vec::new(), next: AtomicUsize::new(0) }),
+        }
+    }
+
+    #[test]
+    fn write_recovers_after_a_panic() {
+        let pool = pool();
+        let panicked = block_on(pool.write(|db| -> std::result::Result<(), AppError> {
+            db.conn.execute_batch("BEGIN; INSERT INTO settings (key, value) VALUES ('left', 'open');")?;
+            panic!("writer panicked mid-transaction");
+        }));
+        assert!(matches!(panicked, Err(AppError::Internal { .. })));
+
+        let saved = block_on(pool.write(|db| -> std::result::Result<_, AppError> {
+            db.save_setting("theme", "dark")?;
+            Ok(db.get_setting("left")?)
+        }))
+        .unwrap();
+        assert_eq!(saved, None);
+        assert!(!pool.writer.is_poisoned());
+        assert!(block_on(pool.write(|db| Ok::<_, AppError>(db.conn.is_autocommit()))).unwrap());
+    }
+}
@@ -0,0 +1,293 @@
+use rusqlite::{Result, Row, params};
+use serde::{Deserialize, Serialize};
+use chrono::{Duration, Local, NaiveDate};
+use uuid::Uuid;
+
+use super::{Color, Database, DueDate, Priority, Project, Task, TaskStatus, Timestamp};
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectTemplateTask {
+    pub title: String,
+    pub description: String,
+    pub priority: Priority,
+    // Days after the anchor date; `None` for tasks without a due date
+    pub due_offset_days: Option<i64>,
+    // Time part of RFC 3339 due dates (e.g. `T17:00:00+02:00`), kept as-is
+    pub due_time: Option<String>,
+    pub subtasks: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+// Snapshot of a project that can be stamped out again. Due dates are stored
+// relative to an anchor so a release checklist moves with the release date.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectTemplate {
+    pub id: i32,
+    pub name: String,
+    pub color: Color,
+    pub description: Option<String>,
+    pub tasks: Vec<ProjectTemplateTask>,
+    pub created_at: String,
+}
+
+const PROJECT_TEMPLATE_COLUMNS: &str = "id, name, color, description, tasks, created_at";
+
+fn project_template_from_row(row: &Row) -> Result<ProjectTemplate> {
+    let tasks: String = row.get(4)?;
+    Ok(ProjectTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        color: row.get(2)?,
+        description: row.get(3)?,
+        tasks: serde_json::from_str(&tasks).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        created_at: row.get(5)?,
+    })
+}
+
+// Splits a due date into its day and, for an exact time, the `T...` suffix
+// of its RFC 3339 form
+fn split_due(due_date: &DueDate) -> (NaiveDate, Option<String>) {
+    let time = match due_date {
+        DueDate::Day(_) => None,
+        DueDate::At(_) => due_date.to_string().get(10..).map(str::to_string),
+    };
+    (due_date.date(), time)
+}
+
+// Inverse of `split_due`
+fn join_due(date: NaiveDate, time: Option<&str>) -> DueDate {
+    time.and_then(|time| format!("{}{}", date.format("%Y-%m-%d"), time).parse().ok())
+        .unwrap_or(DueDate::Day(date))
+}
+
+fn parse_anchor(anchor_date: Option<&str>) -> std::result::Result<Option<NaiveDate>, AppError> {
+    anchor_date
+        .map(|anchor| {
+            NaiveDate::parse_from_str(anchor, "%Y-%m-%d").map_err(|_| {
+                AppError::invalid_field(
+                    "anchor_date",
+                    format!("anchor_date must be a date (YYYY-MM-DD), not '{}'", anchor),
+                )
+            })
+        })
+        .transpose()
+}
+
+impl Database {
+    // Captures a project's tasks. Offsets are taken from `anchor_date`, which
+    // defaults to the earliest due date in the project.
+    pub fn save_project_as_template(
+        &self,
+        project_id: i32,
+        name: Option<&str>,
+        anchor_date: Option<&str>,
+    ) -> std::result::Result<ProjectTemplate, AppError> {
+        let project = self.conn.query_row(
+            "SELECT id, name, color, description, folder_id FROM projects WHERE id = ?1 AND deleted_at IS NULL",
+            [project_id],
+            |row| {
+                Ok(Project {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    color: row.get(2)?,
+                    description: row.get(3)?,
+                    folder_id: row.get(4)?,
+                })
+            },
+        )?;
+
+        let tasks = {
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT {} FROM tasks WHERE project_id = ?1 AND deleted_at IS NULL ORDER BY created_at",
+                Self::TASK_COLUMNS
+            ))?;
+            stmt.query_map([project_id], Self::task_from_row)?
+                .collect::<Result<Vec<_>>>()?
+        };
+        let tasks = self.with_details(tasks)?;
+
+        let anchor = match parse_anchor(anchor_date)? {
+            Some(anchor) => anchor,
+            None => tasks
+                .iter()
+                .filter_map(|t| t.task.due_date.as_ref().map(split_due))
+                .map(|(date, _)| date)
+                .min()
+                .unwrap_or_else(|| Local::now().date_naive()),
+        };
+
+        let template_tasks: Vec<ProjectTemplateTask> = tasks
+            .into_iter()
+            .map(|details| {
+                let due = details.task.due_date.as_ref().map(split_due);
+                ProjectTemplateTask {
+                    title: details.task.title,
+                    description: details.task.description,
+                    priority: details.task.priority,
+                    due_offset_days: due.as_ref().map(|(date, _)| (*date - anchor).num_days()),
+                    due_time: due.and_then(|(_, time)| time),
+                    subtasks: details.subtasks.into_iter().map(|s| s.text).collect(),
+                    tags: details.tags,
+                }
+            })
+            .collect();
+
+        let tasks_json = serde_json::to_string(&template_tasks)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.conn.execute(
+            "INSERT INTO project_templates (name, color, description, tasks, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                name.map(str::trim).unwrap_or(&project.name),
+                project.color,
+                project.description,
+                tasks_json,
+                Timestamp::now()
+            ],
+        )?;
+        Ok(self.get_project_template(self.conn.last_insert_rowid() as i32)?)
+    }
+
+    pub fn get_project_template(&self, id: i32) -> Result<ProjectTemplate> {
+        self.conn.query_row(
+            &format!("SELECT {} FROM project_templates WHERE id = ?1", PROJECT_TEMPLATE_COLUMNS),
+            [id],
+            project_template_from_row,
+        )
+    }
+
+    pub fn list_project_templates(&self) -> Result<Vec<ProjectTemplate>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM project_templates ORDER BY name",
+            PROJECT_TEMPLATE_COLUMNS
+        ))?;
+        let templates = stmt.query_map([], project_template_from_row)?;
+        templates.collect()
+    }
+
+    pub fn delete_project_template(&self, id: i32) -> Result<()> {
+        self.conn.execute("DELETE FROM project_templates WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    // Creates a new project in `folder_id` with every task of the template,
+    // due dates counted from `anchor_date` (today by default). Either the
+    // whole project is created or nothing is.
+    pub fn instantiate_project_template(
+        &self,
+        id: i32,
+        folder_id: Option<i32>,
+        anchor_date: Option<&str>,
+        name: Option<&str>,
+    ) -> std::result::Result<Project, AppError> {
+        let template = self.get_project_template(id)?;
+        let anchor = parse_anchor(anchor_date)?.unwrap_or_else(|| Local::now().date_naive());
+
+        self.transaction(|db| {
+            let project = Project {
+                id: 0,
+                name: name
+                    .map(str::trim)
+                    .filter(|n| !n.is_empty())
+                    .unwrap_or(&template.name)
+                    .to_string(),
+                color: template.color,
+                description: template.description,
+                folder_id,
+            };
+            let project = Project { id: db.save_project(&project)?, ..project };
+
+            for template_task in &template.tasks {
+                let due_date = template_task
+                    .due_offset_days
+                    .map(|offset| {
+                        Duration::try_days(offset)
+                            .and_then(|offset| anchor.checked_add_signed(offset))
+                            .map(|date| join_due(date, template_task.due_time.as_deref()))
+                            .ok_or_else(|| {
+                                AppError::invalid_field(
+                                    "anchor_date",
+                                    format!("'{}' falls outside the supported date range", template_task.title),
+                                )
+                            })
+                    })
+                    .transpose()?;
+                let now = Timestamp::now();
+                let task = Task {
+                    id: Uuid::new_v4().to_string(),
+                    title: template_task.title.clone(),
+                    description: template_task.description.clone(),
+                    due_date,
+                    priority: template_task.priority,
+                    completed: false,
+                    status: TaskStatus::Todo,
+                    column_id: None,
+                    project_id: Some(project.id),
+                    recurrence: None,
+                    series_id: None,
+                    created_at: now,
+                    updated_at: now,
+                    completed_at: None,
+                };
+                db.create_task(&task, &template_task.subtasks, &template_task.tags)?;
+            }
+
+            Ok(project)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template_with_offset(db: &Database, offset: i64) -> i32 {
+        let tasks = vec![ProjectTemplateTask {
+            title: "Launch".to_string(),
+            description: String::new(),
+            priority: Priority::Medium,
+            due_offset_days: Some(offset),
+            due_time: None,
+            subtasks: Vec::new(),
+            tags: Vec::new(),
+        }];
+        db.conn
+            .execute(
+                "INSERT INTO project_templates (name, color, description, tasks, created_at)
+                 VALUES ('Release', '#3b82f6', '', ?1, ?2)",
+                params![serde_json::to_string(&tasks).unwrap(), Timestamp::now()],
+            )
+            .unwrap();
+        db.conn.last_insert_rowid() as i32
+    }
+
+    #[test]
+    fn offsets_past_the_date_range_are_rejected() {
+        let db = Database::open_in_memory().unwrap();
+        let count = |db: &Database| -> i64 {
+            db.conn.query_row("SELECT COUNT(*) FROM projects", [], |row| row.get(0)).unwrap()
+        };
+        let before = count(&db);
+        for offset in [i64::MAX, 1_000_000_000] {
+            let id = template_with_offset(&db, offset);
+            let err = db.instantiate_project_template(id, None, Some("2026-01-01"), None).unwrap_err();
+            assert!(matches!(err, AppError::Validation { .. }), "{:?}", err);
+        }
+        assert_eq!(count(&db), before);
+    }
+
+    #[test]
+    fn offsets_are_counted_from_the_anchor() {
+        let db = Database::open_in_memory().unwrap();
+        let id = template_with_offset(&db, 3);
+        let project = db.instantiate_project_template(id, None, Some("2026-01-30"), None).unwrap();
+        let due: String = db
+            .conn
+            .query_row("SELECT due_date FROM tasks WHERE project_id = ?1", [project.id], |row| row.get(0))
+            .unwrap();
+        assert_eq!(due, "2026-02-02");
+    }
+}
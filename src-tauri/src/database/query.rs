@@ -0,0 +1,545 @@
+use rusqlite::{Result, params_from_iter};
+use rusqlite::types::Value;
+use std::fmt;
+use chrono::{Duration, Local, NaiveDate};
+
+use super::saved_searches::overdue_condition;
+use super::search::to_fts_query;
+use super::{Database, Priority, TaskStatus, TaskWithDetails};
+
+// Task query language used by the search bar, e.g.
+//
+//   tag:work priority:high due:<2026-11-01 project:"Health" -completed has:subtasks
+//
+// Terms are separated by whitespace and must all match. A leading `-` negates
+// a term, values containing spaces are double-quoted, `#work` is short for
+// `tag:work`, and anything else that is not a `key:value` filter is matched
+// against the full-text index.
+
+const KEYS: &str = "tag, priority, due, project, status, has";
+const HAS_VALUES: &str = "subtasks, tags, due, description, recurrence, reminders";
+
+// Commands report this as an `AppError`
+#[derive(Debug)]
+pub enum QueryError {
+    // `start`/`end` are character offsets of the offending term
+    Parse { message: String, start: usize, end: usize },
+    Database(rusqlite::Error),
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::Parse { message, start, .. } => {
+                write!(f, "{} (at character {})", message, start + 1)
+            }
+            QueryError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for QueryError {
+    fn from(e: rusqlite::Error) -> Self {
+        QueryError::Database(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            Comparison::Eq => "=",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Text(String),
+    Phrase(String),
+    Tag(String),
+    Priority(Priority),
+    Due(Comparison, NaiveDate),
+    // Open tasks due before the given day
+    Overdue(NaiveDate),
+    NoDueDate,
+    Project(Option<String>),
+    Status(TaskStatus),
+    Completed,
+    Has(Field),
+}
+
+// Optional task data that `has:` checks for
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Subtasks,
+    Tags,
+    DueDate,
+    Description,
+    Recurrence,
+    Reminders,
+}
+
+impl Field {
+    fn condition(&self) -> &'static str {
+        match self {
+            Field::Subtasks => "EXISTS (SELECT 1 FROM subtasks WHERE subtasks.task_id = t.id)",
+            Field::Tags => "EXISTS (SELECT 1 FROM tags WHERE tags.task_id = t.id)",
+            Field::DueDate => "t.due_date IS NOT NULL",
+            Field::Description => "t.description <> ''",
+            Field::Recurrence => "t.recurrence IS NOT NULL",
+            Field::Reminders => "EXISTS (SELECT 1 FROM reminders WHERE reminders.task_id = t.id)",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    negated: bool,
+    filter: Filter,
+}
+
+// One whitespace-separated piece of the query before it is interpreted
+struct Token {
+    negated: bool,
+    key: Option<String>,
+    value: String,
+    quoted: bool,
+    start: usize,
+    end: usize,
+}
+
+fn parse_error(message: String, start: usize, end: usize) -> QueryError {
+    QueryError::Parse { message, start, end }
+}
+
+fn tokenize(input: &str) -> std::result::Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        let negated = chars[i] == '-' && chars.get(i + 1).is_some_and(|c| !c.is_whitespace());
+        if negated {
+            i += 1;
+        }
+
+        let mut key = None;
+        if chars[i] != '"' {
+            let word_start = i;
+            while i < chars.len() && !chars[i].is_whitespace() && chars[i] != ':' {
+                i += 1;
+            }
+            let word: String = chars[word_start..i].iter().collect();
+            if i < chars.len() && chars[i] == ':' {
+                key = Some(word.to_lowercase());
+                i += 1;
+            } else {
+                tokens.push(Token { negated, key, value: word, quoted: false, start, end: i });
+                continue;
+            }
+        }
+
+        let quoted = i < chars.len() && chars[i] == '"';
+        let value = if quoted {
+            let value_start = i + 1;
+            i = value_start;
+            while i < chars.len() && chars[i] != '"' {
+                i += 1;
+            }
+            if i == chars.len() {
+                return Err(parse_error("Unterminated quote".to_string(), start, i));
+            }
+            i += 1;
+            chars[value_start..i - 1].iter().collect()
+        } else {
+            let value_start = i;
+            while i < chars.len() && !chars[i].is_whitespace() {
+                i += 1;
+            }
+            chars[value_start..i].iter().collect::<String>()
+        };
+
+        if let Some(key) = &key
+            && value.is_empty()
+        {
+            return Err(parse_error(format!("Missing value for '{}:'", key), start, i));
+        }
+        tokens.push(Token { negated, key, value, quoted, start, end: i });
+    }
+
+    Ok(tokens)
+}
+
+fn parse_date(value: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match value.to_lowercase().as_str() {
+        "today" => Some(today),
+        "tomorrow" => Some(today + Duration::days(1)),
+        "yesterday" => Some(today - Duration::days(1)),
+        _ => NaiveDate::parse_from_str(value, "%Y-%m-%d").ok(),
+    }
+}
+
+fn parse_term(token: Token, today: NaiveDate) -> std::result::Result<Term, QueryError> {
+    let Token { negated, key, value, quoted, start, end } = token;
+    let invalid = |message: String| parse_error(message, start, end);
+
+    let filter = match key.as_deref() {
+        None if quoted && value.trim().is_empty() => {
+            return Err(invalid("Empty quoted phrase".to_string()));
+        }
+        None if quoted => Filter::Phrase(value),
+        None if value.eq_ignore_ascii_case("completed") => Filter::Completed,
+        None if value.len() > 1 && value.starts_with('#') => Filter::Tag(value[1..].to_string()),
+        None => Filter::Text(value),
+        Some("tag") => Filter::Tag(value.trim_start_matches('#').to_string()),
+        Some("priority") => Filter::Priority(value.parse().map_err(invalid)?),
+        Some("due") => {
+            if value.eq_ignore_ascii_case("none") {
+                Filter::NoDueDate
+            } else if value.eq_ignore_ascii_case("overdue") {
+                Filter::Overdue(today)
+            } else {
+                let (comparison, date) = [
+                    ("<=", Comparison::Le),
+                    (">=", Comparison::Ge),
+                    ("<", Comparison::Lt),
+                    (">", Comparison::Gt),
+                    ("=", Comparison::Eq),
+                ]
+                .iter()
+                .find_map(|(op, cmp)| value.strip_prefix(op).map(|rest| (*cmp, rest)))
+                .unwrap_or((Comparison::Eq, value.as_str()));
+                let date = parse_date(date, today).ok_or_else(|| {
+                    invalid(format!(
+                        "Invalid due date '{}'; use YYYY-MM-DD, today, tomorrow, yesterday, overdue or none",
+                        date
+                    ))
+                })?;
+                Filter::Due(comparison, date)
+            }
+        }
+        Some("project") => {
+            if value.eq_ignore_ascii_case("none") && !quoted {
+                Filter::Project(None)
+            } else {
+                Filter::Project(Some(value))
+            }
+        }
+        Some("status") => {
+            let status = match value.to_lowercase().as_str() {
+                "todo" => TaskStatus::Todo,
+                "in-progress" | "in_progress" | "doing" => TaskStatus::InProgress,
+                "done" => TaskStatus::Done,
+                // The sidebar's completed/incomplete filters
+                "completed" => return Ok(Term { negated, filter: Filter::Completed }),
+                "incomplete" => return Ok(Term { negated: !negated, filter: Filter::Completed }),
+                _ => {
+                    return Err(invalid(format!(
+                        "Unknown status '{}'; expected todo, in-progress, done, completed or incomplete",
+                        value
+                    )));
+                }
+            };
+            Filter::Status(status)
+        }
+        Some("has") => {
+            let field = match value.to_lowercase().as_str() {
+                "subtasks" => Field::Subtasks,
+                "tags" => Field::Tags,
+                "due" => Field::DueDate,
+                "description" => Field::Description,
+                "recurrence" => Field::Recurrence,
+                "reminders" => Field::Reminders,
+                _ => {
+                    return Err(invalid(format!(
+                        "Unknown field '{}' for has:; expected one of {}",
+                        value, HAS_VALUES
+                    )));
+                }
+            };
+            Filter::Has(field)
+        }
+        Some(other) => {
+            return Err(invalid(format!(
+                "Unknown filter '{}:'; expected one of {}",
+                other, KEYS
+            )));
+        }
+    };
+
+    Ok(Term { negated, filter })
+}
+
+fn parse(input: &str, today: NaiveDate) -> std::result::Result<Vec<Term>, QueryError> {
+    tokenize(input)?
+        .into_iter()
+        .map(|token| parse_term(token, today))
+        .collect()
+}
+
+// Builds a WHERE clause over `tasks t`; every user value becomes a bound
+// parameter.
+fn compile(terms: &[Term]) -> (String, Vec<Value>) {
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+
+    for term in terms {
+        let mut bind = |value: Value| {
+            values.push(value);
+            format!("?{}", values.len())
+        };
+
+        let condition = match &term.filter {
+            // Stray punctuation has nothing to match in the index
+            Filter::Text(text) if !text.chars().any(char::is_alphanumeric) => continue,
+            Filter::Text(text) => match to_fts_query(text) {
+                Some(fts) => format!(
+                    "t.rowid IN (SELECT rowid FROM task_search WHERE task_search MATCH {})",
+                    bind(Value::Text(fts))
+                ),
+                None => continue,
+            },
+            Filter::Phrase(phrase) => format!(
+                "t.rowid IN (SELECT rowid FROM task_search WHERE task_search MATCH {})",
+                bind(Value::Text(format!("\"{}\"", phrase.replace('"', "\"\""))))
+            ),
+            Filter::Tag(tag) => format!(
+                "EXISTS (SELECT 1 FROM tags WHERE tags.task_id = t.id AND tags.tag = {} COLLATE NOCASE)",
+                bind(Value::Text(tag.clone()))
+            ),
+            Filter::Priority(priority) => {
+                format!("t.priority = {}", bind(Value::Text(priority.to_string())))
+            }
+            Filter::Due(comparison, date) => format!(
+                "(t.due_date IS NOT NULL AND substr(t.due_date, 1, 10) {} {})",
+                comparison.as_sql(),
+                bind(Value::Text(date.format("%Y-%m-%d").to_string()))
+            ),
+            Filter::Overdue(today) => {
+                overdue_condition(&bind(Value::Text(today.format("%Y-%m-%d").to_string())))
+            }
+            Filter::NoDueDate => "t.due_date IS NULL".to_string(),
+            Filter::Project(Some(name)) => format!(
+                "t.project_id IN (SELECT id FROM projects WHERE name = {} COLLATE NOCASE AND deleted_at IS NULL)",
+                bind(Value::Text(name.clone()))
+            ),
+            Filter::Project(None) => "t.project_id IS NULL".to_string(),
+            Filter::Status(status) => {
+                format!("t.status = {}", bind(Value::Text(status.as_str().to_string())))
+            }
+            Filter::Completed => "t.completed = 1".to_string(),
+            Filter::Has(field) => field.condition().to_string(),
+        };
+
+        conditions.push(if term.negated {
+            format!("NOT ({})", condition)
+        } else {
+            condition
+        });
+    }
+
+    if conditions.is_empty() {
+        ("1 = 1".to_string(), values)
+    } else {
+        (conditions.join(" AND "), values)
+    }
+}
+
+impl Database {
+    pub fn query_tasks(&self, query: &str) -> std::result::Result<Vec<TaskWithDetails>, QueryError> {
+        let terms = parse(query, Local::now().date_naive())?;
+        let (condition, values) = compile(&terms);
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM tasks t WHERE t.deleted_at IS NULL AND ({}) ORDER BY t.created_at DESC",
+            Self::TASK_COLUMNS,
+            condition
+        ))?;
+        let tasks = stmt
+            .query_map(params_from_iter(values), Self::task_from_row)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(self.with_details(tasks)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Task;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 15).unwrap()
+    }
+
+    // (negated, key, value, quoted, start, end) for each token
+    fn tokens(input: &str) -> Vec<(bool, Option<String>, String, bool, usize, usize)> {
+        tokenize(input)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.negated, t.key, t.value, t.quoted, t.start, t.end))
+            .collect()
+    }
+
+    fn compiled(input: &str) -> (String, Vec<Value>) {
+        compile(&parse(input, today()).unwrap())
+    }
+
+    fn error(input: &str) -> (String, usize, usize) {
+        match parse(input, today()) {
+            Err(QueryError::Parse { message, start, end }) => (message, start, end),
+            other => panic!("expected a parse error for {:?}, got {:?}", input, other),
+        }
+    }
+
+    fn text(value: &str) -> Value {
+        Value::Text(value.to_string())
+    }
+
+    #[test]
+    fn tokenizer_splits_keys_quotes_and_negation() {
+        assert_eq!(
+            tokens(r#"  -Tag:Work "two words" project:"My Project" -"#),
+            vec![
+                (true, Some("tag".to_string()), "Work".to_string(), false, 2, 11),
+                (false, None, "two words".to_string(), true, 12, 23),
+                (false, Some("project".to_string()), "My Project".to_string(), true, 24, 44),
+                (false, None, "-".to_string(), false, 45, 46),
+            ]
+        );
+        assert!(tokens(" \t ").is_empty());
+    }
+
+    #[test]
+    fn tokenizer_errors_point_at_the_term() {
+        assert!(matches!(
+            tokenize(r#"tag:a "open"#),
+            Err(QueryError::Parse { start: 6, end: 11, .. })
+        ));
+        assert_eq!(error("due: tag:a"), ("Missing value for 'due:'".to_string(), 0, 4));
+    }
+
+    #[test]
+    fn parse_errors_carry_the_span_of_the_bad_term() {
+        let (message, start, end) = error("groceries priority:urgent");
+        assert!(message.contains("urgent"), "{}", message);
+        assert_eq!((start, end), (10, 25));
+
+        let (message, start, end) = error("due:<someday");
+        assert!(message.contains("'someday'"), "{}", message);
+        assert_eq!((start, end), (0, 12));
+
+        let (_, start, end) = error("status:blocked");
+        assert_eq!((start, end), (0, 14));
+        assert!(error("has:parents").0.contains("has:"));
+        assert!(error("colour:red").0.starts_with("Unknown filter 'colour:'"));
+        assert_eq!(error(r#"a "" b"#), ("Empty quoted phrase".to_string(), 2, 4));
+        assert_eq!(error(r#""  ""#).0, "Empty quoted phrase");
+        assert!(QueryError::Parse { message: "Bad".to_string(), start: 4, end: 6 }
+            .to_string()
+            .ends_with("(at character 5)"));
+    }
+
+    #[test]
+    fn filters_compile_to_bound_conditions() {
+        assert_eq!(compiled(""), ("1 = 1".to_string(), vec![]));
+        assert_eq!(
+            compiled("tag:#Work priority:HIGH"),
+            (
+                "EXISTS (SELECT 1 FROM tags WHERE tags.task_id = t.id AND tags.tag = ?1 COLLATE NOCASE) AND t.priority = ?2"
+                    .to_string(),
+                vec![text("Work"), text("high")]
+            )
+        );
+        assert_eq!(
+            compiled("due:<=tomorrow -due:yesterday"),
+            (
+                "(t.due_date IS NOT NULL AND substr(t.due_date, 1, 10) <= ?1) AND \
+                 NOT ((t.due_date IS NOT NULL AND substr(t.due_date, 1, 10) = ?2))"
+                    .to_string(),
+                vec![text("2026-10-16"), text("2026-10-14")]
+            )
+        );
+        assert_eq!(
+            compiled(r#"project:none project:"none" due:none"#),
+            (
+                "t.project_id IS NULL AND t.project_id IN (SELECT id FROM projects WHERE name = ?1 COLLATE NOCASE AND deleted_at IS NULL) AND t.due_date IS NULL"
+                    .to_string(),
+                vec![text("none")]
+            )
+        );
+        assert_eq!(
+            compiled("status:doing has:reminders -completed"),
+            (
+                "t.status = ?1 AND EXISTS (SELECT 1 FROM reminders WHERE reminders.task_id = t.id) AND NOT (t.completed = 1)"
+                    .to_string(),
+                vec![text("in-progress")]
+            )
+        );
+    }
+
+    #[test]
+    fn text_and_phrases_use_the_search_index() {
+        let (sql, values) = compiled(r#"report "say hi" ?!"#);
+        assert_eq!(
+            sql,
+            "t.rowid IN (SELECT rowid FROM task_search WHERE task_search MATCH ?1) AND \
+             t.rowid IN (SELECT rowid FROM task_search WHERE task_search MATCH ?2)"
+        );
+        assert_eq!(values, [text(&to_fts_query("report").unwrap()), text("\"say hi\"")]);
+    }
+
+    #[test]
+    fn sidebar_filters_are_accepted() {
+        assert_eq!(compiled("status:completed"), ("t.completed = 1".to_string(), vec![]));
+        assert_eq!(compiled("status:incomplete"), ("NOT (t.completed = 1)".to_string(), vec![]));
+        assert_eq!(compiled("-status:incomplete"), ("t.completed = 1".to_string(), vec![]));
+        assert_eq!(
+            compiled("due:overdue"),
+            (overdue_condition("?1"), vec![text("2026-10-15")])
+        );
+        assert_eq!(compiled("#urgent"), compiled("tag:urgent"));
+        assert_eq!(compiled("-#urgent").0, format!("NOT ({})", compiled("tag:urgent").0));
+        // A lone `#` is just text
+        assert_eq!(parse("#", today()).unwrap(), vec![Term { negated: false, filter: Filter::Text("#".to_string()) }]);
+    }
+
+    #[test]
+    fn query_tasks_applies_the_filters() {
+        let db = Database::open_in_memory().unwrap();
+        let today = Local::now().date_naive();
+        let due = |days: i64| Some(crate::database::DueDate::Day(today + Duration::days(days)));
+        db.create_task(&Task { due_date: due(-1), ..Task::sample("late") }, &[], &["urgent".into()]).unwrap();
+        db.create_task(&Task { due_date: due(1), ..Task::sample("soon") }, &[], &[]).unwrap();
+        db.create_task(&Task { due_date: due(-1), completed: true, ..Task::sample("done") }, &[], &[]).unwrap();
+
+        let ids = |query: &str| -> Vec<String> {
+            let mut ids: Vec<String> = db.query_tasks(query).unwrap().into_iter().map(|t| t.task.id).collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids("due:overdue"), ["late"]);
+        assert_eq!(ids("#urgent"), ["late"]);
+        assert_eq!(ids("status:incomplete"), ["late", "soon"]);
+        assert_eq!(ids("status:completed"), ["done"]);
+        assert_eq!(ids(r#""soon""#), ["soon"]);
+    }
+}
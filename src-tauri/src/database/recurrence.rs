@@ -0,0 +1,331 @@
+use rusqlite::{OptionalExtension, Result, params};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+use chrono::{Datelike, Days, Local, Months, NaiveDate, Weekday};
+use uuid::Uuid;
+
+use super::stats::on_time;
+use super::{Database, DueDate, Subtask, Task, TaskStatus, Timestamp};
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+// RRULE-style recurrence stored as JSON on the task. `until` is an inclusive
+// `YYYY-MM-DD` date and `count` the total number of occurrences in the series.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    #[serde(default = "default_interval")]
+    pub interval: u32,
+    // Weekly only; empty repeats on the weekday of the current due date
+    #[serde(default)]
+    pub weekdays: Vec<Weekday>,
+    // Monthly only; defaults to the day of the current due date
+    pub month_day: Option<u32>,
+    pub until: Option<NaiveDate>,
+    pub count: Option<u32>,
+}
+
+// Longest gap between occurrences, e.g. every 100 years
+const MAX_INTERVAL: u32 = 1200;
+
+fn default_interval() -> u32 {
+    1
+}
+
+impl ToSql for RecurrenceRule {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        Ok(ToSqlOutput::from(json))
+    }
+}
+
+impl FromSql for RecurrenceRule {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        serde_json::from_str(value.as_str()?).map_err(|e| FromSqlError::Other(Box::new(e)))
+    }
+}
+
+impl RecurrenceRule {
+    pub fn validate(&self) -> std::result::Result<(), AppError> {
+        if self.interval == 0 || self.interval > MAX_INTERVAL {
+            return Err(AppError::invalid_field(
+                "recurrence.interval",
+                format!("Interval must be between 1 and {}", MAX_INTERVAL),
+            ));
+        }
+        if self.month_day.is_some_and(|day| !(1..=31).contains(&day)) {
+            return Err(AppError::invalid_field("recurrence.month_day", "Day of the month must be between 1 and 31"));
+        }
+        if self.count == Some(0) {
+            return Err(AppError::invalid_field("recurrence.count", "Count must be at least 1"));
+        }
+        Ok(())
+    }
+
+    // Due date of the occurrence after `current`, or `None` once the series
+    // has ended. Time of day is preserved; a missing due date counts as today.
+    pub fn next_due_date(&self, current: Option<&DueDate>, occurrence: u32) -> Option<DueDate> {
+        if let Some(count) = self.count
+            && occurrence >= count
+        {
+            return None;
+        }
+
+        let interval = self.interval.max(1);
+        let current = current.copied().unwrap_or(DueDate::Day(Local::now().date_naive()));
+        let base = current.date();
+        // A series that would run past the last representable date ends
+        let next = match self.frequency {
+            Frequency::Daily => base.checked_add_days(Days::new(interval as u64))?,
+            Frequency::Weekly => self.next_weekly(base, interval)?,
+            Frequency::Monthly => add_months(base, interval, self.month_day.unwrap_or(base.day()))?,
+            Frequency::Yearly => add_months(base, interval.checked_mul(12)?, base.day())?,
+        };
+
+        if let Some(until) = self.until
+            && next > until
+        {
+            return None;
+        }
+
+        Some(current.with_date(next))
+    }
+
+    fn next_weekly(&self, base: NaiveDate, interval: u32) -> Option<NaiveDate> {
+        if self.weekdays.is_empty() {
+            return base.checked_add_days(Days::new(7 * interval as u64));
+        }
+
+        // Only weeks that are a multiple of `interval` away from the base
+        // week are eligible: the rest of the base week comes first, then
+        // the week `interval` weeks on.
+        let later_this_week = base
+            .iter_days()
+            .skip(1)
+            .take_while(|day| day.weekday() != Weekday::Mon)
+            .find(|day| self.weekdays.contains(&day.weekday()));
+        if later_this_week.is_some() {
+            return later_this_week;
+        }
+        let next_week = base
+            .week(Weekday::Mon)
+            .checked_first_day()?
+            .checked_add_days(Days::new(7 * interval as u64))?;
+        next_week.iter_days().take(7).find(|day| self.weekdays.contains(&day.weekday()))
+    }
+}
+
+// Adds whole months, clamping `day` to the length of the target month so a
+// series on the 31st lands on the 30th or 28th/29th in shorter months.
+fn add_months(base: NaiveDate, months: u32, day: u32) -> Option<NaiveDate> {
+    let first = base.with_day(1)?.checked_add_months(Months::new(months))?;
+    let last_day = first.checked_add_months(Months::new(1))?.pred_opt()?.day();
+    first.with_day(day.clamp(1, last_day))
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SeriesCompletion {
+    pub task_id: String,
+    pub occurrence: u32,
+    pub due_date: Option<String>,
+    pub completed_at: String,
+    pub on_time: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SeriesHistory {
+    pub series_id: String,
+    pub completions: Vec<SeriesCompletion>,
+    // Consecutive on-time completions ending with the most recent one
+    pub current_streak: u32,
+    pub longest_streak: u32,
+}
+
+impl Database {
+    // Reconciles completion history with the task's current state: a newly
+    // completed occurrence is recorded and its successor spawned, a reopened
+    // one has its history entry removed. Safe to call repeatedly.
+    pub(crate) fn sync_recurrence(&self, task_id: &str) -> Result<()> {
+        let row: Option<(bool, Option<String>, u32)> = self.conn
+            .query_row(
+                "SELECT completed, series_id, occurrence FROM tasks WHERE id = ?1 AND recurrence IS NOT NULL",
+                [task_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+        let Some((completed, Some(series_id), occurrence)) = row else {
+            return Ok(());
+        };
+
+        if !completed {
+            self.conn.execute("DELETE FROM task_completions WHERE task_id = ?1", [task_id])?;
+            return Ok(());
+        }
+
+        let inserted = self.conn.execute(
+            "INSERT OR IGNORE INTO task_completions (task_id, series_id, occurrence, due_date, completed_at)
+             SELECT id, series_id, occurrence, due_date, COALESCE(completed_at, ?2) FROM tasks WHERE id = ?1",
+            params![task_id, Timestamp::now()],
+        )?;
+        if inserted == 0 {
+            return Ok(());
+        }
+
+        let successor_exists: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM tasks WHERE series_id = ?1 AND occurrence > ?2)",
+            params![series_id, occurrence],
+            |row| row.get(0),
+        )?;
+        if successor_exists {
+            return Ok(());
+        }
+
+        self.spawn_next_occurrence(task_id, occurrence)
+    }
+
+    fn spawn_next_occurrence(&self, task_id: &str, occurrence: u32) -> Result<()> {
+        let template = self.conn.query_row(
+            &format!("SELECT {} FROM tasks WHERE id = ?1", Self::TASK_COLUMNS),
+            [task_id],
+            Self::task_from_row,
+        )?;
+        let Some(rule) = template.recurrence.clone() else {
+            return Ok(());
+        };
+        let Some(next_due) = rule.next_due_date(template.due_date.as_ref(), occurrence) else {
+            return Ok(());
+        };
+
+        let now = Timestamp::now();
+        let next = Task {
+            id: Uuid::new_v4().to_string(),
+            due_date: Some(next_due),
+            completed: false,
+            status: TaskStatus::Todo,
+            column_id: None,
+            created_at: now,
+            updated_at: now,
+            completed_at: None,
+            ..template
+        };
+        self.write_task(&next)?;
+        self.conn.execute(
+            "UPDATE tasks SET occurrence = ?1 WHERE id = ?2",
+            params![occurrence + 1, next.id],
+        )?;
+
+        for subtask in self.get_subtasks_for_task(task_id)? {
+            self.save_subtask(&Subtask {
+                id: Uuid::new_v4().to_string(),
+                task_id: next.id.clone(),
+                text: subtask.text,
+                completed: false,
+                position: subtask.position,
+            })?;
+        }
+        let tags = self.get_tags_for_task(task_id)?;
+        self.save_tags_for_task(&next.id, &tags)?;
+        self.copy_relative_reminders(task_id, &next.id)
+    }
+
+    pub fn get_series_history(&self, series_id: &str) -> Result<SeriesHistory> {
+        let mut stmt = self.conn.prepare(
+            "SELECT task_id, occurrence, due_date, completed_at FROM task_completions
+             WHERE series_id = ?1 ORDER BY occurrence"
+        )?;
+        let completions = stmt
+            .query_map([series_id], |row| {
+                let due_date: Option<DueDate> = row.get(2)?;
+                let completed_at: Timestamp = row.get(3)?;
+                Ok(SeriesCompletion {
+                    task_id: row.get(0)?,
+                    occurrence: row.get(1)?,
+                    due_date: due_date.map(|due| due.to_string()),
+                    completed_at: completed_at.to_string(),
+                    on_time: due_date.is_none_or(|due| on_time(&due, completed_at.instant())),
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut longest_streak = 0;
+        let mut run = 0;
+        for completion in &completions {
+            run = if completion.on_time { run + 1 } else { 0 };
+            longest_streak = longest_streak.max(run);
+        }
+
+        Ok(SeriesHistory {
+            series_id: series_id.to_string(),
+            completions,
+            current_streak: run,
+            longest_streak,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(frequency: Frequency, interval: u32) -> RecurrenceRule {
+        RecurrenceRule { frequency, interval, weekdays: Vec::new(), month_day: None, until: None, count: None }
+    }
+
+    fn day(value: &str) -> DueDate {
+        DueDate::Day(value.parse().unwrap())
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_fields() {
+        let field = |rule: RecurrenceRule| match rule.validate() {
+            Err(AppError::Validation { field, .. }) => field,
+            other => panic!("expected a validation error, got {:?}", other),
+        };
+
+        assert!(rule(Frequency::Daily, 1).validate().is_ok());
+        assert_eq!(field(rule(Frequency::Daily, 0)).as_deref(), Some("recurrence.interval"));
+        assert_eq!(field(rule(Frequency::Daily, u32::MAX)).as_deref(), Some("recurrence.interval"));
+        let month_day = RecurrenceRule { month_day: Some(32), ..rule(Frequency::Monthly, 1) };
+        assert_eq!(field(month_day).as_deref(), Some("recurrence.month_day"));
+        let count = RecurrenceRule { count: Some(0), ..rule(Frequency::Daily, 1) };
+        assert_eq!(field(count).as_deref(), Some("recurrence.count"));
+    }
+
+    #[test]
+    fn until_must_be_a_date() {
+        let json = r#"{"frequency":"daily","month_day":null,"until":"next week","count":null}"#;
+        assert!(serde_json::from_str::<RecurrenceRule>(json).is_err());
+    }
+
+    #[test]
+    fn next_due_date_ends_instead_of_overflowing() {
+        let last = day(&NaiveDate::MAX.to_string());
+        for frequency in [Frequency::Daily, Frequency::Weekly, Frequency::Monthly, Frequency::Yearly] {
+            assert_eq!(rule(frequency, u32::MAX).next_due_date(Some(&day("2026-01-31")), 1), None);
+            assert_eq!(rule(frequency, 1).next_due_date(Some(&last), 1), None);
+        }
+    }
+
+    #[test]
+    fn weekly_on_weekdays_skips_to_the_interval_week() {
+        let rule = RecurrenceRule { weekdays: vec![Weekday::Mon, Weekday::Thu], ..rule(Frequency::Weekly, 2) };
+        // Monday 2026-10-12: Thursday of the same week, then Monday two weeks on
+        assert_eq!(rule.next_due_date(Some(&day("2026-10-12")), 1), Some(day("2026-10-15")));
+        assert_eq!(rule.next_due_date(Some(&day("2026-10-15")), 1), Some(day("2026-10-26")));
+    }
+
+    #[test]
+    fn monthly_clamps_to_the_end_of_short_months() {
+        let rule = RecurrenceRule { month_day: Some(31), ..rule(Frequency::Monthly, 1) };
+        assert_eq!(rule.next_due_date(Some(&day("2026-01-31")), 1), Some(day("2026-02-28")));
+    }
+}
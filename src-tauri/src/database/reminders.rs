@@ -0,0 +1,375 @@
+use rusqlite::{OptionalExtension, Result, Row, params};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use uuid::Uuid;
+
+use super::{Database, DueDate, Timestamp};
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReminderStatus {
+    Pending,
+    Fired,
+    Dismissed,
+}
+
+impl ReminderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReminderStatus::Pending => "pending",
+            ReminderStatus::Fired => "fired",
+            ReminderStatus::Dismissed => "dismissed",
+        }
+    }
+}
+
+impl ToSql for ReminderStatus {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for ReminderStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "pending" => Ok(ReminderStatus::Pending),
+            "fired" => Ok(ReminderStatus::Fired),
+            "dismissed" => Ok(ReminderStatus::Dismissed),
+            other => Err(FromSqlError::Other(format!("Unknown reminder status: {}", other).into())),
+        }
+    }
+}
+
+// A reminder either fires at an absolute time (`remind_at`) or a number of
+// minutes before the task's due date (`offset_minutes`). `fire_at` is the
+// effective UTC time, moved by snoozing and by due date changes; it is `None`
+// for relative reminders on tasks without a due date.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Reminder {
+    pub id: String,
+    pub task_id: String,
+    pub remind_at: Option<Timestamp>,
+    pub offset_minutes: Option<i64>,
+    pub fire_at: Option<Timestamp>,
+    pub status: ReminderStatus,
+    pub fired_at: Option<Timestamp>,
+}
+
+// A reminder whose time has come, joined with what the notification shows
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DueReminder {
+    pub reminder_id: String,
+    pub task_id: String,
+    pub task_title: String,
+    pub due_date: Option<DueDate>,
+    pub fire_at: Timestamp,
+    // Fired later than scheduled, e.g. because the app was closed
+    pub missed: bool,
+}
+
+// Longest snooze, and furthest a relative reminder can sit from its due date
+const MAX_SNOOZE_MINUTES: i64 = 7 * 24 * 60;
+const MAX_OFFSET_MINUTES: i64 = 5 * 366 * 24 * 60;
+
+const REMINDER_COLUMNS: &str = "id, task_id, remind_at, offset_minutes, fire_at, status, fired_at";
+
+fn reminder_from_row(row: &Row) -> Result<Reminder> {
+    Ok(Reminder {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        remind_at: row.get(2)?,
+        offset_minutes: row.get(3)?,
+        fire_at: row.get(4)?,
+        status: row.get(5)?,
+        fired_at: row.get(6)?,
+    })
+}
+
+// Reminder times are stored to the second with a `Z` suffix, unlike
+// `Timestamp`, because the scheduler compares them as text
+fn format_instant(instant: DateTime<Utc>) -> String {
+    instant.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+// `None` as well when the offset lands outside the representable range
+fn relative_fire_at(due_date: Option<&DueDate>, offset_minutes: i64) -> Option<String> {
+    let due = due_date.and_then(DueDate::instant)?;
+    due.checked_sub_signed(Duration::try_minutes(offset_minutes)?).map(format_instant)
+}
+
+impl Database {
+    pub fn add_reminder(
+        &self,
+        task_id: &str,
+        remind_at: Option<&str>,
+        offset_minutes: Option<i64>,
+    ) -> std::result::Result<Reminder, AppError> {
+        let due_date: Option<DueDate> = self.conn
+            .query_row(
+                "SELECT due_date FROM tasks WHERE id = ?1 AND deleted_at IS NULL",
+                [task_id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| AppError::not_found("task", task_id))?;
+        let remind_at = remind_at
+            .map(|at| {
+                DateTime::parse_from_rfc3339(at)
+                    .map(|instant| format_instant(instant.with_timezone(&Utc)))
+                    .map_err(|e| {
+                        AppError::invalid_field("remind_at", format!("remind_at must be an RFC 3339 timestamp: {}", e))
+                    })
+            })
+            .transpose()?;
+        let fire_at = match (&remind_at, offset_minutes) {
+            (Some(at), None) => Some(at.clone()),
+            (None, Some(offset)) => {
+                if !(-MAX_OFFSET_MINUTES..=MAX_OFFSET_MINUTES).contains(&offset) {
+                    return Err(AppError::invalid_field(
+                        "offset_minutes",
+                        format!("offset_minutes must be between -{0} and {0}", MAX_OFFSET_MINUTES),
+                    ));
+                }
+                relative_fire_at(due_date.as_ref(), offset)
+            }
+            _ => {
+                return Err(AppError::invalid("Exactly one of remind_at or offset_minutes must be set"));
+            }
+        };
+        Ok(self.insert_reminder(task_id, remind_at.as_deref(), offset_minutes, fire_at)?)
+    }
+
+    fn insert_reminder(
+        &self,
+        task_id: &str,
+        remind_at: Option<&str>,
+        offset_minutes: Option<i64>,
+        fire_at: Option<String>,
+    ) -> Result<Reminder> {
+        let id = Uuid::new_v4().to_string();
+        self.conn.execute(
+            "INSERT INTO reminders (id, task_id, remind_at, offset_minutes, fire_at, status)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![id, task_id, remind_at, offset_minutes, fire_at, ReminderStatus::Pending],
+        )?;
+        self.conn.query_row(
+            &format!("SELECT {} FROM reminders WHERE id = ?1", REMINDER_COLUMNS),
+            [&id],
+            reminder_from_row,
+        )
+    }
+
+    pub fn get_reminders_for_task(&self, task_id: &str) -> Result<Vec<Reminder>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM reminders WHERE task_id = ?1 ORDER BY fire_at",
+            REMINDER_COLUMNS
+        ))?;
+        let reminders = stmt.query_map([task_id], reminder_from_row)?;
+        reminders.collect()
+    }
+
+    pub fn delete_reminder(&self, reminder_id: &str) -> Result<()> {
+        self.conn.execute("DELETE FROM reminders WHERE id = ?1", [reminder_id])?;
+        Ok(())
+    }
+
+    pub fn snooze_reminder(&self, reminder_id: &str, minutes: i64) -> std::result::Result<(), AppError> {
+        if !(1..=MAX_SNOOZE_MINUTES).contains(&minutes) {
+            return Err(AppError::invalid_field(
+                "minutes",
+                format!("Snooze must be between 1 and {} minutes", MAX_SNOOZE_MINUTES),
+            ));
+        }
+        let fire_at = Utc::now()
+            .checked_add_signed(Duration::minutes(minutes))
+            .ok_or_else(|| AppError::invalid_field("minutes", "Snooze ends too far in the future"))?;
+        let updated = self.conn.execute(
+            "UPDATE reminders SET fire_at = ?1, status = ?2, fired_at = NULL WHERE id = ?3",
+            params![format_instant(fire_at), ReminderStatus::Pending, reminder_id],
+        )?;
+        if updated == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows.into());
+        }
+        Ok(())
+    }
+
+    pub fn dismiss_reminder(&self, reminder_id: &str) -> Result<()> {
+        let updated = self.conn.execute(
+            "UPDATE reminders SET status = ?1 WHERE id = ?2",
+            params![ReminderStatus::Dismissed, reminder_id],
+        )?;
+        if updated == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        Ok(())
+    }
+
+    // Recomputes pending relative reminders after a task's due date changed
+    pub(crate) fn reschedule_relative_reminders(&self, task_id: &str) -> Result<()> {
+        let due_date: Option<DueDate> = self.conn
+            .query_row("SELECT due_date FROM tasks WHERE id = ?1", [task_id], |row| row.get(0))
+            .optional()?
+            .flatten();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, offset_minutes FROM reminders
+             WHERE task_id = ?1 AND offset_minutes IS NOT NULL AND status = ?2"
+        )?;
+        let relative = stmt
+            .query_map(params![task_id, ReminderStatus::Pending], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        for (id, offset) in relative {
+            self.conn.execute(
+                "UPDATE reminders SET fire_at = ?1 WHERE id = ?2",
+                params![relative_fire_at(due_date.as_ref(), offset), id],
+            )?;
+        }
+        Ok(())
+    }
+
+    // Gives the next occurrence of a recurring task the same relative reminders
+    pub(crate) fn copy_relative_reminders(&self, from_task_id: &str, to_task_id: &str) -> Result<()> {
+        let offsets = {
+            let mut stmt = self.conn.prepare(
+                "SELECT offset_minutes FROM reminders WHERE task_id = ?1 AND offset_minutes IS NOT NULL"
+            )?;
+            stmt.query_map([from_task_id], |row| row.get::<_, i64>(0))?
+                .collect::<Result<Vec<_>>>()?
+        };
+        let due_date: Option<DueDate> =
+            self.conn.query_row("SELECT due_date FROM tasks WHERE id = ?1", [to_task_id], |row| row.get(0))?;
+        for offset in offsets {
+            self.insert_reminder(to_task_id, None, Some(offset), relative_fire_at(due_date.as_ref(), offset))?;
+        }
+        Ok(())
+    }
+
+    // Marks every pending reminder due at `now` as fired and returns them.
+    // Reminders on completed or trashed tasks are skipped rather than delivered.
+    pub fn take_due_reminders(&self, now: DateTime<Utc>) -> Result<Vec<DueReminder>> {
+        let now_str = format_instant(now);
+        self.transaction(|db| {
+            let due = {
+                let mut stmt = db.conn.prepare(
+                    "SELECT r.id, r.task_id, t.title, t.due_date, r.fire_at
+                     FROM reminders r JOIN tasks t ON t.id = r.task_id
+                     WHERE r.status = ?1 AND r.fire_at IS NOT NULL AND r.fire_at <= ?2
+                       AND t.completed = 0 AND t.deleted_at IS NULL
+                     ORDER BY r.fire_at"
+                )?;
+                stmt.query_map(params![ReminderStatus::Pending, now_str], |row| {
+                    let fire_at: Timestamp = row.get(4)?;
+                    // Anything more than a minute late was missed while the app was closed
+                    let missed = now - fire_at.instant() > Duration::minutes(1);
+                    Ok(DueReminder {
+                        reminder_id: row.get(0)?,
+                        task_id: row.get(1)?,
+                        task_title: row.get(2)?,
+                        due_date: row.get(3)?,
+                        fire_at,
+                        missed,
+                    })
+                })?
+                .collect::<Result<Vec<_>>>()?
+            };
+
+            for reminder in &due {
+                db.conn.execute(
+                    "UPDATE reminders SET status = ?1, fired_at = ?2 WHERE id = ?3",
+                    params![ReminderStatus::Fired, now_str, reminder.reminder_id],
+                )?;
+            }
+
+            Ok(due)
+        })
+    }
+
+    // Earliest pending fire time, used by the scheduler to decide how long to sleep
+    pub fn next_reminder_time(&self) -> Result<Option<DateTime<Utc>>> {
+        let next: Option<Timestamp> = self.conn.query_row(
+            "SELECT MIN(r.fire_at) FROM reminders r JOIN tasks t ON t.id = r.task_id
+             WHERE r.status = ?1 AND r.fire_at IS NOT NULL AND t.completed = 0 AND t.deleted_at IS NULL",
+            [ReminderStatus::Pending],
+            |row| row.get(0),
+        )?;
+        Ok(next.map(|at| at.instant()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{Task, Timestamp};
+
+    fn db_with_task(id: &str) -> Database {
+        let db = Database::open_in_memory().unwrap();
+        let task = Task { due_date: Some(DueDate::Day("2030-01-01".parse().unwrap())), ..Task::sample(id) };
+        db.create_task(&task, &[], &[]).unwrap();
+        db
+    }
+
+    fn invalid_field<T: std::fmt::Debug>(result: std::result::Result<T, AppError>) -> Option<String> {
+        match result {
+            Err(AppError::Validation { field, .. }) => field,
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn add_reminder_needs_a_live_task() {
+        let db = db_with_task("t1");
+        let missing = db.add_reminder("missing", Some("2030-01-01T09:00:00Z"), None);
+        assert!(matches!(missing, Err(AppError::NotFound { .. })));
+
+        db.conn.execute("UPDATE tasks SET deleted_at = ?1 WHERE id = 't1'", [Timestamp::now()]).unwrap();
+        let trashed = db.add_reminder("t1", Some("2030-01-01T09:00:00Z"), None);
+        assert!(matches!(trashed, Err(AppError::NotFound { .. })));
+        assert!(db.get_reminders_for_task("t1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn offsets_and_snoozes_are_bounded() {
+        let db = db_with_task("t1");
+        assert_eq!(invalid_field(db.add_reminder("t1", None, Some(i64::MAX))).as_deref(), Some("offset_minutes"));
+        assert_eq!(invalid_field(db.add_reminder("t1", None, Some(i64::MIN))).as_deref(), Some("offset_minutes"));
+
+        let reminder = db.add_reminder("t1", None, Some(MAX_OFFSET_MINUTES)).unwrap();
+        assert!(reminder.fire_at.is_some());
+        assert_eq!(invalid_field(db.snooze_reminder(&reminder.id, i64::MAX)).as_deref(), Some("minutes"));
+        assert_eq!(invalid_field(db.snooze_reminder(&reminder.id, 0)).as_deref(), Some("minutes"));
+        db.snooze_reminder(&reminder.id, MAX_SNOOZE_MINUTES).unwrap();
+    }
+
+    #[test]
+    fn due_reminders_are_taken_once() {
+        let db = db_with_task("t1");
+        let reminder = db.add_reminder("t1", Some("2026-10-01T11:00:00+02:00"), None).unwrap();
+        assert_eq!(reminder.remind_at, Some("2026-10-01T09:00:00Z".parse().unwrap()));
+        assert_eq!(reminder.fire_at, reminder.remind_at);
+        assert_eq!(db.next_reminder_time().unwrap(), reminder.fire_at.map(|at| at.instant()));
+
+        let now = "2026-10-01T09:30:00Z".parse::<Timestamp>().unwrap().instant();
+        let due = db.take_due_reminders(now).unwrap();
+        assert_eq!(due.len(), 1);
+        assert!(due[0].missed);
+        assert_eq!(due[0].due_date, Some(DueDate::Day("2030-01-01".parse().unwrap())));
+        assert!(db.take_due_reminders(now).unwrap().is_empty());
+
+        let fired = &db.get_reminders_for_task("t1").unwrap()[0];
+        assert_eq!(fired.status, ReminderStatus::Fired);
+        assert_eq!(fired.fired_at.map(|at| at.instant()), Some(now));
+    }
+
+    #[test]
+    fn relative_fire_at_out_of_range_is_none() {
+        let due = DueDate::Day("2030-01-01".parse().unwrap());
+        assert_eq!(relative_fire_at(Some(&due), i64::MAX), None);
+        assert_eq!(relative_fire_at(Some(&due), i64::MIN), None);
+        assert!(relative_fire_at(Some(&due), 60).is_some());
+    }
+}
@@ -0,0 +1,386 @@
+use rusqlite::{Result, Row, params, params_from_iter};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, Value, ValueRef};
+use serde::{Deserialize, Serialize};
+use chrono::{Local, Utc};
+
+use super::{Database, Priority, TaskWithDetails};
+
+// Mirrors `FilterBy` in src/types/index.ts
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterBy {
+    #[default]
+    All,
+    Completed,
+    Incomplete,
+    High,
+    Project,
+    Today,
+    Overdue,
+    Urgent,
+}
+
+impl FilterBy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterBy::All => "all",
+            FilterBy::Completed => "completed",
+            FilterBy::Incomplete => "incomplete",
+            FilterBy::High => "high",
+            FilterBy::Project => "project",
+            FilterBy::Today => "today",
+            FilterBy::Overdue => "overdue",
+            FilterBy::Urgent => "urgent",
+        }
+    }
+}
+
+impl ToSql for FilterBy {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for FilterBy {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value.as_str()? {
+            "all" => Ok(FilterBy::All),
+            "completed" => Ok(FilterBy::Completed),
+            "incomplete" => Ok(FilterBy::Incomplete),
+            "high" => Ok(FilterBy::High),
+            "project" => Ok(FilterBy::Project),
+            "today" => Ok(FilterBy::Today),
+            "overdue" => Ok(FilterBy::Overdue),
+            "urgent" => Ok(FilterBy::Urgent),
+            other => Err(FromSqlError::Other(format!("Unknown filter: {}", other).into())),
+        }
+    }
+}
+
+// A smart list. Every set criterion must match; `tags` matches tasks that
+// carry any of the listed tags.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedSearch {
+    pub id: i32,
+    pub name: String,
+    pub tags: Vec<String>,
+    pub priority: Option<Priority>,
+    pub project_id: Option<i32>,
+    pub filter_by: FilterBy,
+    pub completed: Option<bool>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedSearchInput {
+    pub name: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub priority: Option<Priority>,
+    pub project_id: Option<i32>,
+    #[serde(default)]
+    pub filter_by: FilterBy,
+    pub completed: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedSearchCount {
+    pub id: i32,
+    pub count: i64,
+}
+
+const SAVED_SEARCH_COLUMNS: &str = "id, name, tags, priority, project_id, filter_by, completed, created_at";
+
+fn saved_search_from_row(row: &Row) -> Result<SavedSearch> {
+    let tags: String = row.get(2)?;
+    Ok(SavedSearch {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        tags: serde_json::from_str(&tags).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        priority: row.get(3)?,
+        project_id: row.get(4)?,
+        filter_by: row.get(5)?,
+        completed: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+fn tags_json(tags: &[String]) -> Result<String> {
+    serde_json::to_string(tags).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+pub(super) fn overdue_condition(today: &str) -> String {
+    format!(
+        "(t.due_date IS NOT NULL AND substr(t.due_date, 1, 10) < {} AND t.completed = 0)",
+        today
+    )
+}
+
+// WHERE clause over `tasks t` for a saved search. Overdue and today follow the
+// sidebar: dates compare by calendar day in local time, and only open tasks
+// are overdue.
+fn search_condition(search: &SavedSearch) -> (String, Vec<Value>) {
+    let mut conditions = vec!["t.deleted_at IS NULL".to_string()];
+    let mut values = Vec::new();
+    let mut bind = |value: Value| {
+        values.push(value);
+        format!("?{}", values.len())
+    };
+
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    match search.filter_by {
+        FilterBy::All | FilterBy::Project => {}
+        FilterBy::Completed => conditions.push("t.completed = 1".to_string()),
+        FilterBy::Incomplete => conditions.push("t.completed = 0".to_string()),
+        FilterBy::High => conditions.push("t.priority = 'high'".to_string()),
+        FilterBy::Today => {
+            let today = bind(Value::Text(today));
+            conditions.push(format!("substr(t.due_date, 1, 10) = {}", today));
+        }
+        FilterBy::Overdue => {
+            let today = bind(Value::Text(today));
+            conditions.push(overdue_condition(&today));
+        }
+        FilterBy::Urgent => {
+            let today = bind(Value::Text(today));
+            conditions.push(format!(
+                "(t.completed = 0 AND (t.priority = 'high' OR {}))",
+                overdue_condition(&today)
+            ));
+        }
+    }
+
+    if !search.tags.is_empty() {
+        let placeholders: Vec<String> = search
+            .tags
+            .iter()
+            .map(|tag| bind(Value::Text(tag.clone())))
+            .collect();
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM tags WHERE tags.task_id = t.id AND tags.tag COLLATE NOCASE IN ({}))",
+            placeholders.join(", ")
+        ));
+    }
+    if let Some(priority) = search.priority {
+        conditions.push(format!("t.priority = {}", bind(Value::Text(priority.to_string()))));
+    }
+    if let Some(project_id) = search.project_id {
+        conditions.push(format!("t.project_id = {}", bind(Value::Integer(project_id as i64))));
+    }
+    if let Some(completed) = search.completed {
+        conditions.push(format!("t.completed = {}", bind(Value::Integer(completed as i64))));
+    }
+
+    (conditions.join(" AND "), values)
+}
+
+impl Database {
+    pub fn create_saved_search(&self, input: &SavedSearchInput) -> Result<SavedSearch> {
+        self.conn.execute(
+            "INSERT INTO saved_searches (name, tags, priority, project_id, filter_by, completed, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                input.name.trim(),
+                tags_json(&input.tags)?,
+                input.priority,
+                input.project_id,
+                input.filter_by,
+                input.completed,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        self.get_saved_search(self.conn.last_insert_rowid() as i32)
+    }
+
+    pub fn get_saved_search(&self, id: i32) -> Result<SavedSearch> {
+        self.conn.query_row(
+            &format!("SELECT {} FROM saved_searches WHERE id = ?1", SAVED_SEARCH_COLUMNS),
+            [id],
+            saved_search_from_row,
+        )
+    }
+
+    pub fn get_saved_searches(&self) -> Result<Vec<SavedSearch>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM saved_searches ORDER BY name",
+            SAVED_SEARCH_COLUMNS
+        ))?;
+        let searches = stmt.query_map([], saved_search_from_row)?;
+        searches.collect()
+    }
+
+    pub fn update_saved_search(&self, id: i32, input: &SavedSearchInput) -> Result<SavedSearch> {
+        let updated = self.conn.execute(
+            "UPDATE saved_searches
+             SET name = ?1, tags = ?2, priority = ?3, project_id = ?4, filter_by = ?5, completed = ?6
+             WHERE id = ?7",
+            params![
+                input.name.trim(),
+                tags_json(&input.tags)?,
+                input.priority,
+                input.project_id,
+                input.filter_by,
+                input.completed,
+                id
+            ],
+        )?;
+        if updated == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        self.get_saved_search(id)
+    }
+
+    pub fn delete_saved_search(&self, id: i32) -> Result<()> {
+        self.conn.execute("DELETE FROM saved_searches WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    // Matching tasks, open ones first and then by due date
+    pub fn evaluate_saved_search(&self, id: i32) -> Result<Vec<TaskWithDetails>> {
+        let search = self.get_saved_search(id)?;
+        let (condition, values) = search_condition(&search);
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM tasks t WHERE {}
+             ORDER BY t.completed, t.due_date IS NULL, t.due_date, t.created_at DESC",
+            Self::TASK_COLUMNS,
+            condition
+        ))?;
+        let tasks = stmt
+            .query_map(params_from_iter(values), Self::task_from_row)?
+            .collect::<Result<Vec<_>>>()?;
+        self.with_details(tasks)
+    }
+
+    // Number of matching tasks for every saved search, for the sidebar badges
+    pub fn get_saved_search_counts(&self) -> Result<Vec<SavedSearchCount>> {
+        let mut counts = Vec::new();
+        for search in self.get_saved_searches()? {
+            let (condition, values) = search_condition(&search);
+            let count = self.conn.query_row(
+                &format!("SELECT COUNT(*) FROM tasks t WHERE {}", condition),
+                params_from_iter(values),
+                |row| row.get(0),
+            )?;
+            counts.push(SavedSearchCount { id: search.id, count });
+        }
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{DueDate, Task, Timestamp};
+    use chrono::Duration;
+
+    fn search(filter_by: FilterBy) -> SavedSearchInput {
+        SavedSearchInput {
+            name: filter_by.as_str().to_string(),
+            tags: Vec::new(),
+            priority: None,
+            project_id: None,
+            filter_by,
+            completed: None,
+        }
+    }
+
+    // Open tasks due yesterday, today and tomorrow (the last one high
+    // priority), plus a completed and a trashed task that are overdue
+    fn db_with_dated_tasks() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        let today = Local::now().date_naive();
+        let due = |days: i64| Some(DueDate::Day(today + Duration::days(days)));
+        let tasks = [
+            Task { due_date: due(-1), priority: Priority::Low, ..Task::sample("yesterday") },
+            Task { due_date: due(0), project_id: Some(1), ..Task::sample("today") },
+            Task { due_date: due(1), priority: Priority::High, ..Task::sample("tomorrow") },
+            Task { due_date: due(-1), priority: Priority::High, completed: true, ..Task::sample("done") },
+            Task { due_date: due(-1), ..Task::sample("trashed") },
+        ];
+        for task in &tasks {
+            db.create_task(task, &[], &[format!("{}-tag", task.id)]).unwrap();
+        }
+        db.conn.execute("UPDATE tasks SET deleted_at = ?1 WHERE id = 'trashed'", [Timestamp::now()]).unwrap();
+        db
+    }
+
+    fn matching(db: &Database, input: &SavedSearchInput) -> Vec<String> {
+        let saved = db.create_saved_search(input).unwrap();
+        db.evaluate_saved_search(saved.id).unwrap().into_iter().map(|t| t.task.id).collect()
+    }
+
+    #[test]
+    fn date_filters_follow_the_sidebar() {
+        let db = db_with_dated_tasks();
+
+        assert_eq!(matching(&db, &search(FilterBy::Today)), ["today"]);
+        assert_eq!(matching(&db, &search(FilterBy::Overdue)), ["yesterday"]);
+        // Open tasks that are overdue or high priority, soonest first
+        assert_eq!(matching(&db, &search(FilterBy::Urgent)), ["yesterday", "tomorrow"]);
+        assert_eq!(matching(&db, &search(FilterBy::High)), ["tomorrow", "done"]);
+        assert_eq!(matching(&db, &search(FilterBy::All)), ["yesterday", "today", "tomorrow", "done"]);
+        assert_eq!(matching(&db, &search(FilterBy::Completed)), ["done"]);
+    }
+
+    #[test]
+    fn criteria_combine() {
+        let db = db_with_dated_tasks();
+
+        let tagged = SavedSearchInput {
+            tags: vec!["TODAY-tag".to_string(), "tomorrow-tag".to_string()],
+            ..search(FilterBy::Incomplete)
+        };
+        assert_eq!(matching(&db, &tagged), ["today", "tomorrow"]);
+        let in_project = SavedSearchInput { project_id: Some(1), ..search(FilterBy::Project) };
+        assert_eq!(matching(&db, &in_project), ["today"]);
+        let low = SavedSearchInput { priority: Some(Priority::Low), ..search(FilterBy::All) };
+        assert_eq!(matching(&db, &low), ["yesterday"]);
+        let completed = SavedSearchInput { completed: Some(true), ..search(FilterBy::Urgent) };
+        assert!(matching(&db, &completed).is_empty());
+    }
+
+    #[test]
+    fn counts_match_the_evaluated_searches() {
+        let db = db_with_dated_tasks();
+        for filter_by in [FilterBy::Today, FilterBy::Overdue, FilterBy::Urgent, FilterBy::All] {
+            db.create_saved_search(&search(filter_by)).unwrap();
+        }
+
+        let counts = db.get_saved_search_counts().unwrap();
+        assert_eq!(counts.len(), 4);
+        for count in counts {
+            assert_eq!(count.count as usize, db.evaluate_saved_search(count.id).unwrap().len());
+        }
+        let by_name: Vec<(String, i64)> = db
+            .get_saved_searches()
+            .unwrap()
+            .into_iter()
+            .zip(db.get_saved_search_counts().unwrap())
+            .map(|(search, count)| (search.name, count.count))
+            .collect();
+        assert_eq!(
+            by_name,
+            [("all".to_string(), 4), ("overdue".to_string(), 1), ("today".to_string(), 1), ("urgent".to_string(), 2)]
+        );
+    }
+
+    fn input(priority: &str) -> serde_json::Result<SavedSearchInput> {
+        serde_json::from_value(serde_json::json!({ "name": "Focus", "priority": priority, "project_id": null, "completed": null }))
+    }
+
+    #[test]
+    fn priority_is_parsed() {
+        assert!(input("urgent").is_err());
+        let input = input(" High ").unwrap();
+        assert_eq!(input.priority, Some(Priority::High));
+
+        let db = Database::open_in_memory().unwrap();
+        let saved = db.create_saved_search(&input).unwrap();
+        assert_eq!(saved.priority, Some(Priority::High));
+        let (condition, values) = search_condition(&saved);
+        assert!(condition.contains("t.priority = "));
+        assert!(values.contains(&Value::Text("high".to_string())));
+    }
+}
@@ -0,0 +1,122 @@
+use rusqlite::{Result, params};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{Database, Task, TaskWithDetails};
+
+// Most results returned by one search
+const MAX_SEARCH_LIMIT: u32 = 500;
+
+const HIGHLIGHT_OPEN: &str = "<mark>";
+const HIGHLIGHT_CLOSE: &str = "</mark>";
+
+// Column weights for bm25(): title, description, subtasks, tags
+const RANK: &str = "bm25(task_search, 10.0, 2.0, 1.0, 4.0)";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskSearchResult {
+    #[serde(flatten)]
+    pub details: TaskWithDetails,
+    // Lower is better, as returned by bm25()
+    pub rank: f64,
+    // Full title with matches wrapped in <mark> tags
+    pub title_highlight: String,
+    // Short excerpt around the best match in any column
+    pub snippet: String,
+}
+
+// Turns free text into an FTS5 query: every word must match as a prefix, and
+// quoting each term keeps FTS5 operators and punctuation from being parsed.
+pub(super) fn to_fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() { None } else { Some(terms.join(" ")) }
+}
+
+impl Database {
+    pub fn search_tasks(&self, query: &str, limit: u32) -> Result<Vec<TaskSearchResult>> {
+        let Some(fts_query) = to_fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let limit = limit.clamp(1, MAX_SEARCH_LIMIT);
+
+        let sql = format!(
+            "SELECT t.id, {rank}, highlight(task_search, 0, ?2, ?3), snippet(task_search, -1, ?2, ?3, '…', 12)
+             FROM task_search JOIN tasks t ON t.rowid = task_search.rowid
+             WHERE task_search MATCH ?1 AND t.deleted_at IS NULL
+             ORDER BY {rank}
+             LIMIT ?4",
+            rank = RANK
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let hits = stmt
+            .query_map(params![fts_query, HIGHLIGHT_OPEN, HIGHLIGHT_CLOSE, limit], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, f64>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        // The FTS columns share names with the task columns, so the hits'
+        // tasks are loaded in one query of their own
+        let ids: Vec<&str> = hits.iter().map(|(id, ..)| id.as_str()).collect();
+        let ids = serde_json::to_string(&ids)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM tasks WHERE id IN (SELECT value FROM json_each(?1))",
+            Self::TASK_COLUMNS
+        ))?;
+        let mut tasks: HashMap<String, Task> = stmt
+            .query_map([&ids], Self::task_from_row)?
+            .map(|task| task.map(|task| (task.id.clone(), task)))
+            .collect::<Result<_>>()?;
+        let tasks = hits
+            .iter()
+            .map(|(id, ..)| tasks.remove(id).ok_or(rusqlite::Error::QueryReturnedNoRows))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(self
+            .with_details(tasks)?
+            .into_iter()
+            .zip(hits)
+            .map(|(details, (_, rank, title_highlight, snippet))| TaskSearchResult {
+                details,
+                rank,
+                title_highlight,
+                snippet,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, title: &str) -> Task {
+        Task { title: title.to_string(), ..Task::sample(id) }
+    }
+
+    #[test]
+    fn search_returns_ranked_hits_with_details() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_task(&task("a", "Pack for travel"), &["Passport".into()], &["trip".into()]).unwrap();
+        db.create_task(&task("b", "Travel travel travel"), &[], &[]).unwrap();
+        db.create_task(&task("c", "Groceries"), &[], &[]).unwrap();
+
+        let results = db.search_tasks("travel", 50).unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r.details.task.id.as_str()).collect();
+        assert_eq!(ids, ["b", "a"]);
+        assert_eq!(results[1].details.tags, ["trip"]);
+        assert_eq!(results[1].details.subtasks[0].text, "Passport");
+        assert!(results[1].title_highlight.contains("<mark>travel</mark>"));
+
+        // A zero limit still returns the best hit
+        assert_eq!(db.search_tasks("travel", 0).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,327 @@
+use rusqlite::{Result, params};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Days, Duration, Local, NaiveDate, NaiveTime, Utc, Weekday};
+use std::collections::{BTreeMap, HashMap};
+
+use super::{Database, DueDate, Priority, Timestamp};
+use crate::error::AppError;
+
+// Days are local calendar days, weeks start on Monday
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ProductivityStatsRequest {
+    // Inclusive `YYYY-MM-DD` range; the last 30 days by default
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct CompletionSummary {
+    pub completed: i64,
+    // Completed by the due date (on the due day for date-only due dates)
+    pub on_time: i64,
+    pub late: i64,
+    // `on_time / (on_time + late)`; `None` when nothing had a due date
+    pub on_time_rate: Option<f64>,
+    // From creation to completion
+    pub average_lead_time_hours: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CompletionBreakdown {
+    // Project/folder id as text, the tag or the priority; `None` for tasks
+    // without a project or folder
+    pub key: Option<String>,
+    pub name: String,
+    #[serde(flatten)]
+    pub summary: CompletionSummary,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PeriodCount {
+    // The day, or the Monday starting the week
+    pub start: NaiveDate,
+    pub completed: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ProductivityStats {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    #[serde(flatten)]
+    pub summary: CompletionSummary,
+    // Every day and week in the range, including empty ones
+    pub per_day: Vec<PeriodCount>,
+    pub per_week: Vec<PeriodCount>,
+    // Largest first
+    pub by_project: Vec<CompletionBreakdown>,
+    pub by_folder: Vec<CompletionBreakdown>,
+    pub by_tag: Vec<CompletionBreakdown>,
+    pub by_priority: Vec<CompletionBreakdown>,
+}
+
+const DEFAULT_RANGE_DAYS: u64 = 30;
+// Longest range a report covers, about five years
+const MAX_RANGE_DAYS: i64 = 5 * 366;
+
+#[derive(Default)]
+struct Tally {
+    completed: i64,
+    on_time: i64,
+    late: i64,
+    lead_seconds: i64,
+}
+
+impl Tally {
+    fn add(&mut self, completion: &Completion) {
+        self.completed += 1;
+        match completion.on_time {
+            Some(true) => self.on_time += 1,
+            Some(false) => self.late += 1,
+            None => {}
+        }
+        self.lead_seconds += completion.lead_seconds;
+    }
+
+    fn summary(&self) -> CompletionSummary {
+        let with_due = self.on_time + self.late;
+        CompletionSummary {
+            completed: self.completed,
+            on_time: self.on_time,
+            late: self.late,
+            on_time_rate: (with_due > 0).then(|| self.on_time as f64 / with_due as f64),
+            average_lead_time_hours: (self.completed > 0)
+                .then(|| self.lead_seconds as f64 / self.completed as f64 / 3600.0),
+        }
+    }
+}
+
+struct Completion {
+    id: String,
+    day: NaiveDate,
+    // `None` without a due date
+    on_time: Option<bool>,
+    lead_seconds: i64,
+    priority: Priority,
+    project: Option<(i32, String)>,
+    folder: Option<(i32, String)>,
+}
+
+fn parse_date(field: &str, value: Option<&str>) -> std::result::Result<Option<NaiveDate>, AppError> {
+    value
+        .map(|value| {
+            NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| {
+                AppError::invalid_field(field, format!("{} must be a date (YYYY-MM-DD), not '{}'", field, value))
+            })
+        })
+        .transpose()
+}
+
+// Resolves an inclusive `YYYY-MM-DD` range of local days, the last 30 by
+// default
+pub(super) fn date_range(
+    from: Option<&str>,
+    to: Option<&str>,
+) -> std::result::Result<(NaiveDate, NaiveDate), AppError> {
+    let to = parse_date("to", to)?.unwrap_or_else(|| Local::now().date_naive());
+    let from = match parse_date("from", from)? {
+        Some(from) => from,
+        None => to
+            .checked_sub_days(Days::new(DEFAULT_RANGE_DAYS - 1))
+            .ok_or_else(|| AppError::invalid_field("to", "to is out of range"))?,
+    };
+    if from > to {
+        return Err(AppError::invalid_field("from", "from must not be after to"));
+    }
+    if (to - from).num_days() >= MAX_RANGE_DAYS {
+        return Err(AppError::invalid_field(
+            "from",
+            format!("The range can span at most {} days", MAX_RANGE_DAYS),
+        ));
+    }
+    Ok((from, to))
+}
+
+// End of a local day, i.e. the start of the next one
+pub(super) fn day_end(day: NaiveDate) -> std::result::Result<Timestamp, AppError> {
+    let next = day
+        .checked_add_signed(Duration::days(1))
+        .ok_or_else(|| AppError::invalid_field("to", "to is out of range"))?;
+    Ok(day_start(next))
+}
+
+// Start of a local day, as stored timestamps are compared
+pub(super) fn day_start(day: NaiveDate) -> Timestamp {
+    let midnight = day.and_time(NaiveTime::MIN);
+    let instant = midnight
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|instant| instant.with_timezone(&Utc))
+        .unwrap_or_else(|| midnight.and_utc());
+    Timestamp::from(instant)
+}
+
+pub(super) fn on_time(due_date: &DueDate, completed_at: DateTime<Utc>) -> bool {
+    match due_date {
+        DueDate::Day(day) => completed_at.with_timezone(&Local).date_naive() <= *day,
+        DueDate::At(instant) => completed_at <= instant.with_timezone(&Utc),
+    }
+}
+
+fn week_start(day: NaiveDate) -> NaiveDate {
+    day.week(Weekday::Mon).checked_first_day().unwrap_or(day)
+}
+
+type Tallies = HashMap<Option<String>, (String, Tally)>;
+
+fn tally(tallies: &mut Tallies, key: Option<String>, name: &str, completion: &Completion) {
+    tallies
+        .entry(key)
+        .or_insert_with(|| (name.to_string(), Tally::default()))
+        .1
+        .add(completion);
+}
+
+fn breakdown(tallies: Tallies) -> Vec<CompletionBreakdown> {
+    let mut rows: Vec<CompletionBreakdown> = tallies
+        .into_iter()
+        .map(|(key, (name, tally))| CompletionBreakdown { key, name, summary: tally.summary() })
+        .collect();
+    rows.sort_by(|a, b| b.summary.completed.cmp(&a.summary.completed).then_with(|| a.name.cmp(&b.name)));
+    rows
+}
+
+impl Database {
+    // Completed tasks in the trash are left out
+    pub fn get_productivity_stats(
+        &self,
+        request: &ProductivityStatsRequest,
+    ) -> std::result::Result<ProductivityStats, AppError> {
+        let (from, to) = date_range(request.from.as_deref(), request.to.as_deref())?;
+        let (start, end) = (day_start(from), day_end(to)?);
+
+        let in_range = "t.completed = 1 AND t.deleted_at IS NULL
+             AND t.completed_at >= ?1 AND t.completed_at < ?2";
+        let completions = {
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT t.id, t.created_at, t.completed_at, t.due_date, t.priority,
+                        p.id, p.name, f.id, f.name
+                 FROM tasks t
+                 LEFT JOIN projects p ON p.id = t.project_id
+                 LEFT JOIN folders f ON f.id = p.folder_id
+                 WHERE {}",
+                in_range
+            ))?;
+            let rows = stmt.query_map(params![start, end], |row| {
+                let created_at: Timestamp = row.get(1)?;
+                let completed_at: Timestamp = row.get(2)?;
+                let due_date: Option<DueDate> = row.get(3)?;
+                let project: Option<i32> = row.get(5)?;
+                let folder: Option<i32> = row.get(7)?;
+                Ok(Completion {
+                    id: row.get(0)?,
+                    day: completed_at.instant().with_timezone(&Local).date_naive(),
+                    on_time: due_date.map(|due| on_time(&due, completed_at.instant())),
+                    lead_seconds: (completed_at.instant() - created_at.instant()).num_seconds().max(0),
+                    priority: row.get(4)?,
+                    project: project.map(|id| row.get(6).map(|name| (id, name))).transpose()?,
+                    folder: folder.map(|id| row.get(8).map(|name| (id, name))).transpose()?,
+                })
+            })?;
+            rows.collect::<Result<Vec<_>>>()?
+        };
+
+        let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+        {
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT g.task_id, g.tag FROM tags g JOIN tasks t ON t.id = g.task_id WHERE {}",
+                in_range
+            ))?;
+            let rows = stmt.query_map(params![start, end], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            for row in rows {
+                let (task_id, tag): (String, String) = row?;
+                tags.entry(task_id).or_default().push(tag);
+            }
+        }
+
+        let mut overall = Tally::default();
+        let mut per_day: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+        let mut per_week: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+        for day in from.iter_days().take_while(|day| *day <= to) {
+            per_day.insert(day, 0);
+            per_week.insert(week_start(day), 0);
+        }
+
+        let mut by_project = HashMap::new();
+        let mut by_folder = HashMap::new();
+        let mut by_tag = HashMap::new();
+        let mut by_priority = HashMap::new();
+        for completion in &completions {
+            overall.add(completion);
+            *per_day.entry(completion.day).or_default() += 1;
+            *per_week.entry(week_start(completion.day)).or_default() += 1;
+
+            match &completion.project {
+                Some((id, name)) => tally(&mut by_project, Some(id.to_string()), name, completion),
+                None => tally(&mut by_project, None, "Inbox", completion),
+            }
+            match &completion.folder {
+                Some((id, name)) => tally(&mut by_folder, Some(id.to_string()), name, completion),
+                None => tally(&mut by_folder, None, "No folder", completion),
+            }
+            for tag in tags.get(&completion.id).into_iter().flatten() {
+                tally(&mut by_tag, Some(tag.clone()), tag, completion);
+            }
+            let priority = completion.priority.to_string();
+            tally(&mut by_priority, Some(priority.clone()), &priority, completion);
+        }
+
+        let counts = |counts: BTreeMap<NaiveDate, i64>| {
+            counts.into_iter().map(|(start, completed)| PeriodCount { start, completed }).collect()
+        };
+        Ok(ProductivityStats {
+            from,
+            to,
+            summary: overall.summary(),
+            per_day: counts(per_day),
+            per_week: counts(per_week),
+            by_project: breakdown(by_project),
+            by_folder: breakdown(by_folder),
+            by_tag: breakdown(by_tag),
+            by_priority: breakdown(by_priority),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(result: std::result::Result<impl std::fmt::Debug, AppError>) -> Option<String> {
+        match result {
+            Err(AppError::Validation { field, .. }) => field,
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn date_range_defaults_to_the_last_30_days() {
+        let (from, to) = date_range(None, Some("2026-10-30")).unwrap();
+        assert_eq!((from.to_string(), to.to_string()), ("2026-10-01".to_string(), "2026-10-30".to_string()));
+    }
+
+    #[test]
+    fn date_range_is_bounded() {
+        let max = NaiveDate::MAX.to_string();
+        let min = NaiveDate::MIN.to_string();
+        assert_eq!(field(date_range(Some("2000-01-01"), Some("2026-01-01"))).as_deref(), Some("from"));
+        assert_eq!(field(date_range(None, Some(&min))).as_deref(), Some("to"));
+        assert_eq!(field(day_end(NaiveDate::MAX)).as_deref(), Some("to"));
+
+        let db = Database::open_in_memory().unwrap();
+        let request = ProductivityStatsRequest { from: Some(max.clone()), to: Some(max) };
+        assert_eq!(field(db.get_productivity_stats(&request)).as_deref(), Some("to"));
+        let to = NaiveDate::MIN.iter_days().nth(3).unwrap().to_string();
+        let stats = db.get_productivity_stats(&ProductivityStatsRequest { from: Some(min), to: Some(to) }).unwrap();
+        assert_eq!(stats.per_day.len(), 4);
+    }
+}
@@ -0,0 +1,277 @@
+use rusqlite::{Result, Row, params};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use super::{Database, Subtask};
+use crate::error::AppError;
+
+// A task's subtasks are numbered by `position` from 0, without gaps. Lookups
+// that find nothing fail with `QueryReturnedNoRows`, for the caller to name.
+// Subtasks of a task in the trash can't be changed and count as not found.
+
+const SUBTASK_COLUMNS: &str = "id, task_id, text, completed, position";
+
+// Condition on `subtasks` that the parent task isn't in the trash
+const LIVE_TASK: &str = "task_id IN (SELECT id FROM tasks WHERE deleted_at IS NULL)";
+
+impl Database {
+    pub(super) fn subtask_from_row(row: &Row) -> Result<Subtask> {
+        Ok(Subtask {
+            id: row.get(0)?,
+            task_id: row.get(1)?,
+            text: row.get(2)?,
+            completed: row.get(3)?,
+            position: row.get(4)?,
+        })
+    }
+
+    pub fn get_subtask(&self, subtask_id: &str) -> Result<Subtask> {
+        self.conn.query_row(
+            &format!("SELECT {} FROM subtasks WHERE id = ?1", SUBTASK_COLUMNS),
+            [subtask_id],
+            Self::subtask_from_row,
+        )
+    }
+
+    pub fn get_subtasks_for_task(&self, task_id: &str) -> Result<Vec<Subtask>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM subtasks WHERE task_id = ?1 ORDER BY position, rowid",
+            SUBTASK_COLUMNS
+        ))?;
+        let subtasks = stmt.query_map([task_id], Self::subtask_from_row)?;
+        subtasks.collect()
+    }
+
+    pub fn save_subtask(&self, subtask: &Subtask) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO subtasks (id, task_id, text, completed, position) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(id) DO UPDATE SET
+                 text = excluded.text, completed = excluded.completed, position = excluded.position",
+            params![subtask.id, subtask.task_id, subtask.text, subtask.completed, subtask.position],
+        )?;
+        Ok(())
+    }
+
+    // Appends a subtask after the task's last one
+    pub fn add_subtask(&self, task_id: &str, text: &str) -> Result<Subtask> {
+        self.transaction(|db| {
+            db.conn.query_row("SELECT 1 FROM tasks WHERE id = ?1 AND deleted_at IS NULL", [task_id], |_| Ok(()))?;
+            let position: i64 = db.conn.query_row(
+                "SELECT COALESCE(MAX(position) + 1, 0) FROM subtasks WHERE task_id = ?1",
+                [task_id],
+                |row| row.get(0),
+            )?;
+            let subtask = Subtask {
+                id: Uuid::new_v4().to_string(),
+                task_id: task_id.to_string(),
+                text: text.to_string(),
+                completed: false,
+                position,
+            };
+            db.save_subtask(&subtask)?;
+            Ok(subtask)
+        })
+    }
+
+    pub fn rename_subtask(&self, subtask_id: &str, text: &str) -> Result<Subtask> {
+        let updated = self.conn.execute(
+            &format!("UPDATE subtasks SET text = ?1 WHERE id = ?2 AND {}", LIVE_TASK),
+            params![text, subtask_id],
+        )?;
+        self.updated_subtask(subtask_id, updated)
+    }
+
+    pub fn set_subtask_completion(&self, subtask_id: &str, completed: bool) -> Result<Subtask> {
+        let updated = self.conn.execute(
+            &format!("UPDATE subtasks SET completed = ?1 WHERE id = ?2 AND {}", LIVE_TASK),
+            params![completed, subtask_id],
+        )?;
+        self.updated_subtask(subtask_id, updated)
+    }
+
+    pub fn toggle_subtask(&self, subtask_id: &str) -> Result<Subtask> {
+        let updated = self.conn.execute(
+            &format!("UPDATE subtasks SET completed = NOT completed WHERE id = ?1 AND {}", LIVE_TASK),
+            [subtask_id],
+        )?;
+        self.updated_subtask(subtask_id, updated)
+    }
+
+    fn updated_subtask(&self, subtask_id: &str, updated: usize) -> Result<Subtask> {
+        if updated == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        self.get_subtask(subtask_id)
+    }
+
+    // Removes a subtask and closes the gap it leaves
+    pub fn delete_subtask(&self, subtask_id: &str) -> Result<()> {
+        self.transaction(|db| {
+            let subtask = db.get_subtask(subtask_id)?;
+            let deleted = db.conn.execute(&format!("DELETE FROM subtasks WHERE id = ?1 AND {}", LIVE_TASK), [subtask_id])?;
+            if deleted == 0 {
+                return Err(rusqlite::Error::QueryReturnedNoRows);
+            }
+            db.conn.execute(
+                "UPDATE subtasks SET position = position - 1 WHERE task_id = ?1 AND position > ?2",
+                params![subtask.task_id, subtask.position],
+            )?;
+            Ok(())
+        })
+    }
+
+    // Puts a task's subtasks in the given order, which must name each of them
+    // exactly once
+    pub fn reorder_subtasks(
+        &self,
+        task_id: &str,
+        subtask_ids: &[String],
+    ) -> std::result::Result<Vec<Subtask>, AppError> {
+        self.transaction(|db| {
+            db.conn.query_row("SELECT 1 FROM tasks WHERE id = ?1 AND deleted_at IS NULL", [task_id], |_| Ok(()))?;
+            let current: HashSet<String> =
+                db.get_subtasks_for_task(task_id)?.into_iter().map(|s| s.id).collect();
+            let requested: HashSet<&String> = subtask_ids.iter().collect();
+            if requested.len() != subtask_ids.len()
+                || requested.len() != current.len()
+                || !requested.iter().all(|id| current.contains(*id))
+            {
+                return Err(AppError::invalid_field(
+                    "subtask_ids",
+                    "subtask_ids must list each of the task's subtasks exactly once",
+                ));
+            }
+            for (position, subtask_id) in subtask_ids.iter().enumerate() {
+                db.conn.execute(
+                    "UPDATE subtasks SET position = ?1 WHERE id = ?2",
+                    params![position as i64, subtask_id],
+                )?;
+            }
+            Ok(db.get_subtasks_for_task(task_id)?)
+        })
+    }
+
+    // Makes a task's subtasks match the given list of texts, in that order.
+    // Subtasks whose text is still listed keep their id and completion; the
+    // rest are deleted and new texts become incomplete subtasks.
+    pub fn sync_subtasks(&self, task_id: &str, texts: &[String]) -> Result<()> {
+        self.transaction(|db| {
+            let mut existing = db.get_subtasks_for_task(task_id)?;
+            let mut kept = Vec::with_capacity(texts.len());
+            for (position, text) in texts.iter().enumerate() {
+                let subtask = match existing.iter().position(|s| &s.text == text) {
+                    Some(index) => existing.remove(index),
+                    None => Subtask {
+                        id: Uuid::new_v4().to_string(),
+                        task_id: task_id.to_string(),
+                        text: text.clone(),
+                        completed: false,
+                        position: 0,
+                    },
+                };
+                kept.push(Subtask { position: position as i64, ..subtask });
+            }
+            for removed in &existing {
+                db.conn.execute("DELETE FROM subtasks WHERE id = ?1", [&removed.id])?;
+            }
+            for subtask in &kept {
+                db.save_subtask(subtask)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Task;
+
+    fn texts(db: &Database, task_id: &str) -> Vec<(String, bool, i64)> {
+        db.get_subtasks_for_task(task_id)
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.text, s.completed, s.position))
+            .collect()
+    }
+
+    fn owned(texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|text| text.to_string()).collect()
+    }
+
+    #[test]
+    fn subtasks_stay_numbered_without_gaps() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_task(&Task::sample("t1"), &[], &[]).unwrap();
+
+        let a = db.add_subtask("t1", "a").unwrap();
+        let b = db.add_subtask("t1", "b").unwrap();
+        let c = db.add_subtask("t1", "c").unwrap();
+        assert_eq!((a.position, b.position, c.position), (0, 1, 2));
+
+        assert_eq!(db.rename_subtask(&b.id, "b2").unwrap().text, "b2");
+        assert!(db.toggle_subtask(&c.id).unwrap().completed);
+        assert!(!db.set_subtask_completion(&c.id, false).unwrap().completed);
+        assert!(db.set_subtask_completion(&c.id, true).unwrap().completed);
+
+        db.delete_subtask(&a.id).unwrap();
+        assert_eq!(texts(&db, "t1"), [("b2".into(), false, 0), ("c".into(), true, 1)]);
+
+        let reordered = db.reorder_subtasks("t1", &[c.id.clone(), b.id.clone()]).unwrap();
+        assert_eq!(reordered.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(), [c.id.as_str(), b.id.as_str()]);
+        assert_eq!(texts(&db, "t1"), [("c".into(), true, 0), ("b2".into(), false, 1)]);
+
+        for ids in [vec![c.id.clone()], vec![c.id.clone(), c.id.clone()], vec![c.id.clone(), a.id.clone()]] {
+            assert!(matches!(db.reorder_subtasks("t1", &ids), Err(AppError::Validation { .. })));
+        }
+        assert_eq!(texts(&db, "t1"), [("c".into(), true, 0), ("b2".into(), false, 1)]);
+
+        for missing in [db.rename_subtask("nope", "x"), db.toggle_subtask("nope"), db.set_subtask_completion("nope", true)] {
+            assert!(matches!(missing, Err(rusqlite::Error::QueryReturnedNoRows)));
+        }
+        assert!(matches!(db.delete_subtask("nope"), Err(rusqlite::Error::QueryReturnedNoRows)));
+        assert!(matches!(db.add_subtask("nope", "x"), Err(rusqlite::Error::QueryReturnedNoRows)));
+    }
+
+    #[test]
+    fn subtasks_of_trashed_tasks_are_not_found() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_task(&Task::sample("t1"), &owned(&["a"]), &[]).unwrap();
+        let a = db.get_subtasks_for_task("t1").unwrap().remove(0);
+        db.delete_task("t1").unwrap();
+
+        let not_found = |result: Result<Subtask>| matches!(result, Err(rusqlite::Error::QueryReturnedNoRows));
+        assert!(not_found(db.rename_subtask(&a.id, "renamed")));
+        assert!(not_found(db.toggle_subtask(&a.id)));
+        assert!(not_found(db.set_subtask_completion(&a.id, true)));
+        assert!(not_found(db.add_subtask("t1", "b")));
+        assert!(matches!(db.delete_subtask(&a.id), Err(rusqlite::Error::QueryReturnedNoRows)));
+        assert!(matches!(
+            db.reorder_subtasks("t1", std::slice::from_ref(&a.id)),
+            Err(AppError::NotFound { .. })
+        ));
+
+        let kept = db.get_subtask(&a.id).unwrap();
+        assert_eq!((kept.text, kept.completed), (a.text, a.completed));
+    }
+
+    #[test]
+    fn syncing_keeps_the_subtasks_still_listed() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_task(&Task::sample("t1"), &owned(&["a", "b", "c"]), &[]).unwrap();
+        let before = db.get_subtasks_for_task("t1").unwrap();
+        db.toggle_subtask(&before[1].id).unwrap();
+
+        db.sync_subtasks("t1", &owned(&["c", "new", "b"])).unwrap();
+
+        let after = db.get_subtasks_for_task("t1").unwrap();
+        assert_eq!(
+            after.iter().map(|s| (s.text.as_str(), s.completed, s.position)).collect::<Vec<_>>(),
+            [("c", false, 0), ("new", false, 1), ("b", true, 2)]
+        );
+        assert_eq!(after[0].id, before[2].id);
+        assert_eq!(after[2].id, before[1].id);
+        assert!(!before.iter().any(|s| s.id == after[1].id));
+        assert!(matches!(db.get_subtask(&before[0].id), Err(rusqlite::Error::QueryReturnedNoRows)));
+    }
+}
@@ -0,0 +1,286 @@
+use rusqlite::{Result, params_from_iter};
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+
+use super::{Database, TaskWithDetails};
+use crate::error::AppError;
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 500;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskSortField {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    DueDate,
+    Priority,
+    Title,
+}
+
+impl TaskSortField {
+    // SQL expression over `tasks t` that rows are ordered by. It never yields
+    // NULL so that it can be compared against a cursor; tasks without a due
+    // date sort after every dated task.
+    fn key(&self) -> &'static str {
+        match self {
+            TaskSortField::CreatedAt => "t.created_at",
+            TaskSortField::UpdatedAt => "t.updated_at",
+            TaskSortField::DueDate => "IFNULL(t.due_date, '9999-12-31')",
+            TaskSortField::Priority => {
+                "(CASE t.priority WHEN 'high' THEN 3 WHEN 'medium' THEN 2 WHEN 'low' THEN 1 ELSE 0 END)"
+            }
+            TaskSortField::Title => "t.title COLLATE NOCASE",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortDirection {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        }
+    }
+
+    fn comparison(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => ">",
+            SortDirection::Desc => "<",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TaskListRequest {
+    #[serde(default)]
+    pub sort: TaskSortField,
+    #[serde(default)]
+    pub direction: SortDirection,
+    // Defaults to 50, capped at 500
+    pub limit: Option<u32>,
+    // `next_cursor` of the previous page; must be used with the same sort
+    pub cursor: Option<String>,
+    pub project_id: Option<i32>,
+    pub completed: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskPage {
+    pub tasks: Vec<TaskWithDetails>,
+    // `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+// Position after the last row of a page: its sort key and id. Serialized to
+// an opaque string for the frontend.
+#[derive(Debug, Serialize, Deserialize)]
+struct Cursor {
+    sort: TaskSortField,
+    direction: SortDirection,
+    key: serde_json::Value,
+    id: String,
+}
+
+fn invalid_cursor(message: &str) -> AppError {
+    AppError::invalid_field("cursor", format!("Invalid cursor: {}", message))
+}
+
+fn key_to_json(key: Value) -> serde_json::Value {
+    match key {
+        Value::Integer(i) => i.into(),
+        Value::Real(f) => f.into(),
+        Value::Text(s) => s.into(),
+        Value::Null | Value::Blob(_) => serde_json::Value::Null,
+    }
+}
+
+fn key_from_json(key: serde_json::Value) -> std::result::Result<Value, AppError> {
+    match key {
+        serde_json::Value::String(s) => Ok(Value::Text(s)),
+        serde_json::Value::Number(n) => n
+            .as_i64()
+            .map(Value::Integer)
+            .or_else(|| n.as_f64().map(Value::Real))
+            .ok_or_else(|| invalid_cursor("bad sort key")),
+        _ => Err(invalid_cursor("bad sort key")),
+    }
+}
+
+impl Database {
+    // One page of tasks using keyset pagination, so later pages cost the same
+    // as the first and rows inserted meanwhile don't shift the pages.
+    pub fn list_tasks(&self, request: &TaskListRequest) -> std::result::Result<TaskPage, AppError> {
+        let limit = request.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        let key = request.sort.key();
+
+        let mut conditions = vec!["t.deleted_at IS NULL".to_string()];
+        let mut values = Vec::new();
+        let mut bind = |value: Value| {
+            values.push(value);
+            format!("?{}", values.len())
+        };
+
+        if let Some(project_id) = request.project_id {
+            conditions.push(format!("t.project_id = {}", bind(Value::Integer(project_id as i64))));
+        }
+        if let Some(completed) = request.completed {
+            conditions.push(format!("t.completed = {}", bind(Value::Integer(completed as i64))));
+        }
+        if let Some(cursor) = &request.cursor {
+            let cursor: Cursor = serde_json::from_str(cursor)
+                .map_err(|_| invalid_cursor("not a cursor returned by list_tasks"))?;
+            if cursor.sort != request.sort || cursor.direction != request.direction {
+                return Err(invalid_cursor("it belongs to a different sort order"));
+            }
+            let after = bind(key_from_json(cursor.key)?);
+            let after_id = bind(Value::Text(cursor.id));
+            let op = request.direction.comparison();
+            conditions.push(format!(
+                "({key} {op} {after} OR ({key} = {after} AND t.id {op} {after_id}))"
+            ));
+        }
+        let page_size = bind(Value::Integer(limit as i64 + 1));
+
+        let direction = request.direction.as_sql();
+        // The sort key is selected after the task columns
+        let key_index = Self::TASK_COLUMNS.split(',').count();
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}, {key} FROM tasks t WHERE {}
+             ORDER BY {key} {direction}, t.id {direction}
+             LIMIT {page_size}",
+            Self::TASK_COLUMNS,
+            conditions.join(" AND "),
+        ))?;
+        let mut rows = stmt
+            .query_map(params_from_iter(values), |row| {
+                Ok((Self::task_from_row(row)?, row.get::<_, Value>(key_index)?))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        // One extra row was fetched to tell whether another page follows
+        let has_more = rows.len() > limit as usize;
+        rows.truncate(limit as usize);
+        let next_cursor = match rows.last() {
+            Some((task, key)) if has_more => {
+                let cursor = Cursor {
+                    sort: request.sort,
+                    direction: request.direction,
+                    key: key_to_json(key.clone()),
+                    id: task.id.clone(),
+                };
+                Some(
+                    serde_json::to_string(&cursor)
+                        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?,
+                )
+            }
+            _ => None,
+        };
+
+        let tasks = self.with_details(rows.into_iter().map(|(task, _)| task).collect())?;
+        Ok(TaskPage { tasks, next_cursor })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{Priority, Task};
+
+    fn db_with_tasks() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        // Most tasks share a priority and a title (up to case) so that pages
+        // split runs of equal sort keys
+        for i in 0..9 {
+            let task = Task {
+                title: if i % 2 == 0 { "Same".to_string() } else { "same".to_string() },
+                priority: if i == 4 { Priority::High } else { Priority::Medium },
+                ..Task::sample(&format!("task-{}", i))
+            };
+            db.create_task(&task, &[], &[]).unwrap();
+        }
+        db.create_task(&Task::sample("trashed"), &[], &[]).unwrap();
+        db.delete_task("trashed").unwrap();
+        db
+    }
+
+    fn page_through(db: &Database, sort: TaskSortField, direction: SortDirection) -> Vec<String> {
+        let mut ids = Vec::new();
+        let mut cursor = None;
+        loop {
+            let request = TaskListRequest { sort, direction, limit: Some(2), cursor, ..Default::default() };
+            let page = db.list_tasks(&request).unwrap();
+            assert!(page.tasks.len() <= 2);
+            ids.extend(page.tasks.into_iter().map(|task| task.task.id));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return ids,
+            }
+        }
+    }
+
+    #[test]
+    fn pages_cover_ties_exactly_once() {
+        let db = db_with_tasks();
+        let mut expected: Vec<String> = (0..9).map(|i| format!("task-{}", i)).collect();
+        expected.sort();
+
+        for sort in [TaskSortField::Priority, TaskSortField::Title, TaskSortField::DueDate] {
+            for direction in [SortDirection::Asc, SortDirection::Desc] {
+                let ids = page_through(&db, sort, direction);
+                let mut seen = ids.clone();
+                seen.sort();
+                assert_eq!(seen, expected, "{:?} {:?}", sort, direction);
+
+                // Ties are broken by id in the same direction as the key
+                let whole = TaskListRequest { sort, direction, limit: Some(100), ..Default::default() };
+                let all: Vec<String> =
+                    db.list_tasks(&whole).unwrap().tasks.into_iter().map(|task| task.task.id).collect();
+                assert_eq!(ids, all, "{:?} {:?}", sort, direction);
+            }
+        }
+
+        let by_priority = page_through(&db, TaskSortField::Priority, SortDirection::Desc);
+        assert_eq!(by_priority[0], "task-4");
+        assert_eq!(by_priority[1..], ["task-8", "task-7", "task-6", "task-5", "task-3", "task-2", "task-1", "task-0"]);
+    }
+
+    #[test]
+    fn malformed_cursors_are_rejected() {
+        let db = db_with_tasks();
+        let first = db
+            .list_tasks(&TaskListRequest { sort: TaskSortField::Title, limit: Some(2), ..Default::default() })
+            .unwrap();
+        let cursor = first.next_cursor.unwrap();
+
+        let forged = serde_json::json!({
+            "sort": "title", "direction": "desc", "key": {"nested": true}, "id": "task-0",
+        });
+        let cases = [
+            (TaskSortField::Title, "not a cursor".to_string()),
+            (TaskSortField::Title, "{}".to_string()),
+            (TaskSortField::Title, forged.to_string()),
+            // A cursor is only valid for the sort order it came from
+            (TaskSortField::Priority, cursor.clone()),
+        ];
+        for (sort, cursor) in cases {
+            let request = TaskListRequest { sort, limit: Some(2), cursor: Some(cursor.clone()), ..Default::default() };
+            match db.list_tasks(&request) {
+                Err(AppError::Validation { field, .. }) => assert_eq!(field.as_deref(), Some("cursor"), "{}", cursor),
+                other => panic!("{} was accepted: {:?}", cursor, other.map(|page| page.tasks.len())),
+            }
+        }
+
+        let request = TaskListRequest { sort: TaskSortField::Title, limit: Some(2), cursor: Some(cursor), ..Default::default() };
+        assert_eq!(db.list_tasks(&request).unwrap().tasks.len(), 2);
+    }
+}
@@ -0,0 +1,164 @@
+use rusqlite::{Result, Row, params};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Local, Utc};
+
+use super::{Database, Priority};
+
+// Reusable task blueprint. Text fields may contain placeholders that are
+// filled in when the template is instantiated, see `expand_placeholders`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskTemplate {
+    pub id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub title: String,
+    pub task_description: String,
+    pub priority: Priority,
+    pub subtasks: Vec<String>,
+    pub tags: Vec<String>,
+    pub project_id: Option<i32>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskTemplateInput {
+    pub name: String,
+    pub description: Option<String>,
+    pub title: String,
+    #[serde(default)]
+    pub task_description: String,
+    pub priority: Priority,
+    #[serde(default)]
+    pub subtasks: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub project_id: Option<i32>,
+}
+
+// Supported placeholders, all in local time:
+// {{date}} 2026-10-17, {{week}} 2026-W42, {{month}} 2026-10, {{year}} 2026,
+// {{weekday}} Saturday. Unknown placeholders are left as they are.
+pub fn expand_placeholders(text: &str, now: DateTime<Local>) -> String {
+    if !text.contains("{{") {
+        return text.to_string();
+    }
+    text.replace("{{date}}", &now.format("%Y-%m-%d").to_string())
+        .replace("{{week}}", &now.format("%G-W%V").to_string())
+        .replace("{{month}}", &now.format("%Y-%m").to_string())
+        .replace("{{year}}", &now.format("%Y").to_string())
+        .replace("{{weekday}}", &now.format("%A").to_string())
+}
+
+impl TaskTemplate {
+    // Copy of the template with every placeholder filled in
+    pub fn expanded(&self, now: DateTime<Local>) -> TaskTemplate {
+        let expand_all = |items: &[String]| -> Vec<String> {
+            items.iter().map(|item| expand_placeholders(item, now)).collect()
+        };
+        TaskTemplate {
+            title: expand_placeholders(&self.title, now),
+            task_description: expand_placeholders(&self.task_description, now),
+            subtasks: expand_all(&self.subtasks),
+            tags: expand_all(&self.tags),
+            ..self.clone()
+        }
+    }
+}
+
+const TEMPLATE_COLUMNS: &str =
+    "id, name, description, title, task_description, priority, subtasks, tags, project_id, created_at";
+
+fn json_list(row: &Row, index: usize) -> Result<Vec<String>> {
+    let json: String = row.get(index)?;
+    serde_json::from_str(&json).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+fn template_from_row(row: &Row) -> Result<TaskTemplate> {
+    Ok(TaskTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        title: row.get(3)?,
+        task_description: row.get(4)?,
+        priority: row.get(5)?,
+        subtasks: json_list(row, 6)?,
+        tags: json_list(row, 7)?,
+        project_id: row.get(8)?,
+        created_at: row.get(9)?,
+    })
+}
+
+impl Database {
+    pub fn create_template(&self, input: &TaskTemplateInput) -> Result<TaskTemplate> {
+        self.conn.execute(
+            "INSERT INTO templates (name, description, title, task_description, priority, subtasks, tags, project_id, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                input.name.trim(),
+                input.description,
+                input.title,
+                input.task_description,
+                input.priority,
+                to_json(&input.subtasks)?,
+                to_json(&input.tags)?,
+                input.project_id,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        self.get_template(self.conn.last_insert_rowid() as i32)
+    }
+
+    pub fn get_template(&self, id: i32) -> Result<TaskTemplate> {
+        self.conn.query_row(
+            &format!("SELECT {} FROM templates WHERE id = ?1", TEMPLATE_COLUMNS),
+            [id],
+            template_from_row,
+        )
+    }
+
+    pub fn list_templates(&self) -> Result<Vec<TaskTemplate>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM templates ORDER BY name",
+            TEMPLATE_COLUMNS
+        ))?;
+        let templates = stmt.query_map([], template_from_row)?;
+        templates.collect()
+    }
+
+    pub fn delete_template(&self, id: i32) -> Result<()> {
+        self.conn.execute("DELETE FROM templates WHERE id = ?1", [id])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(year: i32, month: u32, day: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn placeholders_are_filled_in() {
+        let text = "{{date}} {{week}} {{month}} {{year}} {{weekday}}";
+        assert_eq!(expand_placeholders(text, at(2026, 10, 17)), "2026-10-17 2026-W42 2026-10 2026 Saturday");
+        // Weeks are ISO weeks, which can belong to the previous year
+        assert_eq!(expand_placeholders(text, at(2027, 1, 1)), "2027-01-01 2026-W53 2027-01 2027 Friday");
+    }
+
+    #[test]
+    fn other_text_is_left_alone() {
+        let now = at(2026, 10, 17);
+        assert_eq!(expand_placeholders("Plain title", now), "Plain title");
+        assert_eq!(expand_placeholders("{{unknown}} and {{ date }}", now), "{{unknown}} and {{ date }}");
+        assert_eq!(expand_placeholders("{{date}}{{date}}", now), "2026-10-172026-10-17");
+    }
+}
@@ -0,0 +1,260 @@
+use rusqlite::{Result, params};
+use serde::{Deserialize, Serialize};
+use chrono::{Duration, Utc};
+
+use super::{Database, Timestamp};
+
+// Deleting a task, project or folder only sets its `deleted_at`. Everything
+// trashed by one delete shares the same timestamp, which is how a project's
+// tasks (or a folder's projects) are found again when it is restored.
+// Subtasks, tags and reminders are left in place and come back with the task.
+
+// Settings key holding how many days items stay in the trash; 0 keeps them
+// until the trash is emptied
+pub const TRASH_RETENTION_SETTING: &str = "trash_retention_days";
+pub const DEFAULT_TRASH_RETENTION_DAYS: i64 = 30;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashKind {
+    Task,
+    Project,
+    Folder,
+}
+
+impl TrashKind {
+    fn table(&self) -> &'static str {
+        match self {
+            TrashKind::Task => "tasks",
+            TrashKind::Project => "projects",
+            TrashKind::Folder => "folders",
+        }
+    }
+}
+
+// An item the user deleted. Tasks and projects deleted along with a parent
+// are not listed separately; restoring the parent restores them too.
+#[derive(Debug, Serialize, Clone)]
+pub struct TrashItem {
+    pub kind: TrashKind,
+    // Task ids as they are, project and folder ids as decimal text
+    pub id: String,
+    // The task title or the project/folder name
+    pub name: String,
+    pub deleted_at: Timestamp,
+    // Number of tasks and projects that will be restored with it
+    pub contents: i64,
+}
+
+impl Database {
+    pub fn list_trash(&self) -> Result<Vec<TrashItem>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT 'task', t.id, t.title, t.deleted_at, 0 FROM tasks t
+             WHERE t.deleted_at IS NOT NULL AND NOT EXISTS (
+                 SELECT 1 FROM projects p WHERE p.id = t.project_id AND p.deleted_at = t.deleted_at
+             )
+             UNION ALL
+             SELECT 'project', CAST(p.id AS TEXT), p.name, p.deleted_at,
+                 (SELECT COUNT(*) FROM tasks t WHERE t.project_id = p.id AND t.deleted_at = p.deleted_at)
+             FROM projects p
+             WHERE p.deleted_at IS NOT NULL AND NOT EXISTS (
+                 SELECT 1 FROM folders f WHERE f.id = p.folder_id AND f.deleted_at = p.deleted_at
+             )
+             UNION ALL
+             SELECT 'folder', CAST(f.id AS TEXT), f.name, f.deleted_at,
+                 (SELECT COUNT(*) FROM projects p WHERE p.folder_id = f.id AND p.deleted_at = f.deleted_at)
+                 + (SELECT COUNT(*) FROM tasks t JOIN projects p ON p.id = t.project_id
+                    WHERE p.folder_id = f.id AND p.deleted_at = f.deleted_at AND t.deleted_at = f.deleted_at)
+             FROM folders f
+             WHERE f.deleted_at IS NOT NULL
+             ORDER BY 4 DESC",
+        )?;
+        let items = stmt.query_map([], |row| {
+            let kind = match row.get::<_, String>(0)?.as_str() {
+                "task" => TrashKind::Task,
+                "project" => TrashKind::Project,
+                _ => TrashKind::Folder,
+            };
+            Ok(TrashItem {
+                kind,
+                id: row.get(1)?,
+                name: row.get(2)?,
+                deleted_at: row.get(3)?,
+                contents: row.get(4)?,
+            })
+        })?;
+        items.collect()
+    }
+
+    // Takes an item out of the trash along with whatever was deleted with it.
+    // A task or project whose parent is still in the trash is moved out of
+    // that parent (to the inbox or the top level).
+    pub fn restore_item(&self, kind: TrashKind, id: &str) -> Result<()> {
+        self.transaction(|db| {
+            let deleted_at: Timestamp = db.conn.query_row(
+                &format!("SELECT deleted_at FROM {} WHERE id = ?1 AND deleted_at IS NOT NULL", kind.table()),
+                [id],
+                |row| row.get(0),
+            )?;
+            match kind {
+                TrashKind::Task => {
+                    db.conn.execute(
+                        "UPDATE tasks SET
+                             deleted_at = NULL,
+                             column_id = CASE WHEN project_id IN (SELECT id FROM projects WHERE deleted_at IS NOT NULL)
+                                 THEN NULL ELSE column_id END,
+                             project_id = CASE WHEN project_id IN (SELECT id FROM projects WHERE deleted_at IS NOT NULL)
+                                 THEN NULL ELSE project_id END
+                         WHERE id = ?1",
+                        [id],
+                    )?;
+                }
+                TrashKind::Project => {
+                    db.conn.execute(
+                        "UPDATE tasks SET deleted_at = NULL WHERE project_id = ?1 AND deleted_at = ?2",
+                        params![id, deleted_at],
+                    )?;
+                    db.conn.execute(
+                        "UPDATE projects SET
+                             deleted_at = NULL,
+                             folder_id = CASE WHEN folder_id IN (SELECT id FROM folders WHERE deleted_at IS NOT NULL)
+                                 THEN NULL ELSE folder_id END
+                         WHERE id = ?1",
+                        [id],
+                    )?;
+                }
+                TrashKind::Folder => {
+                    db.conn.execute(
+                        "UPDATE tasks SET deleted_at = NULL
+                         WHERE deleted_at = ?2 AND project_id IN (
+                             SELECT id FROM projects WHERE folder_id = ?1 AND deleted_at = ?2
+                         )",
+                        params![id, deleted_at],
+                    )?;
+                    db.conn.execute(
+                        "UPDATE projects SET deleted_at = NULL WHERE folder_id = ?1 AND deleted_at = ?2",
+                        params![id, deleted_at],
+                    )?;
+                    db.conn.execute("UPDATE folders SET deleted_at = NULL WHERE id = ?1", [id])?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    // Permanently deletes everything in the trash
+    pub fn empty_trash(&self) -> Result<usize> {
+        self.transaction(|db| db.purge_trash(None))
+    }
+
+    // Permanently deletes items that have been in the trash longer than the
+    // retention setting allows
+    pub fn purge_expired_trash(&self) -> Result<usize> {
+        let days = self.trash_retention_days()?;
+        if days == 0 {
+            return Ok(0);
+        }
+        let cutoff = Timestamp::from(Utc::now() - Duration::days(days));
+        self.transaction(|db| db.purge_trash(Some(&cutoff)))
+    }
+
+    pub fn trash_retention_days(&self) -> Result<i64> {
+        let saved = self.get_setting(TRASH_RETENTION_SETTING)?;
+        Ok(match saved.as_deref().map(parse_retention_days) {
+            Some(Ok(days)) => days,
+            Some(Err(e)) => {
+                log::warn!("Ignoring saved trash retention: {}", e);
+                DEFAULT_TRASH_RETENTION_DAYS
+            }
+            None => DEFAULT_TRASH_RETENTION_DAYS,
+        })
+    }
+
+    // Hard-deletes trashed rows deleted before `cutoff` (all of them without
+    // one). A purged task's subtasks, tags and history go with it. Anything
+    // still kept that points at a purged project or folder is detached from
+    // it first.
+    fn purge_trash(&self, cutoff: Option<&Timestamp>) -> Result<usize> {
+        let expired = "deleted_at IS NOT NULL AND (?1 IS NULL OR deleted_at < ?1)";
+        let mut purged = self.conn.execute(&format!("DELETE FROM tasks WHERE {}", expired), [cutoff])?;
+        let in_purged_project = format!("project_id IN (SELECT id FROM projects WHERE {})", expired);
+        let detached = {
+            let mut stmt = self.conn.prepare(&format!("SELECT id FROM tasks WHERE {}", in_purged_project))?;
+            stmt.query_map([cutoff], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>>>()?
+        };
+        self.with_history("Purge trash", &detached, |db| {
+            db.conn.execute(
+                &format!("UPDATE tasks SET project_id = NULL, column_id = NULL WHERE {}", in_purged_project),
+                [cutoff],
+            )
+        })?;
+        self.conn.execute(
+            &format!(
+                "UPDATE projects SET folder_id = NULL
+                 WHERE folder_id IN (SELECT id FROM folders WHERE {}) AND NOT ({})",
+                expired, expired
+            ),
+            [cutoff],
+        )?;
+        purged += self.conn.execute(&format!("DELETE FROM projects WHERE {}", expired), [cutoff])?;
+        purged += self.conn.execute(&format!("DELETE FROM folders WHERE {}", expired), [cutoff])?;
+        if purged > 0 {
+            log::info!("Purged {} item(s) from the trash", purged);
+        }
+        Ok(purged)
+    }
+}
+
+fn parse_retention_days(value: &str) -> std::result::Result<i64, String> {
+    value
+        .trim()
+        .parse::<i64>()
+        .ok()
+        .filter(|days| *days >= 0)
+        .ok_or_else(|| format!("Trash retention must be a whole number of days, not '{}'", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Task;
+
+    #[test]
+    fn purging_a_project_logs_its_detached_tasks() {
+        let db = Database::open_in_memory().unwrap();
+        let now = Timestamp::now();
+        let task = Task { project_id: Some(1), ..Task::sample("t1") };
+        db.create_task(&task, &[], &[]).unwrap();
+        db.conn.execute("UPDATE projects SET deleted_at = ?1 WHERE id = 1", [now]).unwrap();
+
+        db.empty_trash().unwrap();
+
+        assert_eq!(db.get_task("t1").unwrap().unwrap().task.project_id, None);
+        let history = db.get_task_history("t1").unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].field, "project_id");
+        assert_eq!(history[0].old_value.as_deref(), Some("1"));
+        assert_eq!(history[0].new_value, None);
+        assert_eq!(history[0].source, "Purge trash");
+    }
+
+    #[test]
+    fn purging_a_task_deletes_its_history() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_task(&Task::sample("t1"), &["one".into()], &["a".into()]).unwrap();
+        db.with_history("Delete task", &["t1".to_string()], |db| db.delete_task("t1")).unwrap();
+        assert!(!db.get_task_history("t1").unwrap().is_empty());
+
+        assert_eq!(db.empty_trash().unwrap(), 1);
+
+        for table in ["tasks", "subtasks", "tags", "task_history"] {
+            let column = if table == "tasks" { "id" } else { "task_id" };
+            let left: i64 = db
+                .conn
+                .query_row(&format!("SELECT COUNT(*) FROM {} WHERE {} = 't1'", table, column), [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(left, 0, "{}", table);
+        }
+    }
+}
@@ -0,0 +1,587 @@
+use rusqlite::{ErrorCode, OptionalExtension, Result, params};
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{Database, Timestamp};
+
+// Every undoable command runs through `Database::record`, which snapshots
+// the rows it may touch before and after and stores the ones that changed as
+// one operation. Undo writes the "before" snapshots back, redo the "after"
+// ones. Snapshots hold whole rows (and a task's subtasks and tags) as JSON,
+// so columns added later are covered without changes here. Saved searches,
+// templates, reminders and settings aren't recorded and can't be undone.
+
+// Oldest operations are dropped beyond this
+pub const MAX_OPERATIONS: i64 = 200;
+
+// Something a command is about to change. A task brings the rest of its
+// recurring series along, a project its tasks and a folder its projects.
+// A subtask stands for the task it belongs to, and a workflow (by project
+// id) for the project's board columns.
+#[derive(Debug, Clone)]
+pub enum Target {
+    Task(String),
+    Subtask(String),
+    Project(i32),
+    Folder(i32),
+    Workflow(i32),
+}
+
+// Ordered parents first, which is the order rows must be written back in
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+enum Kind {
+    Folder,
+    Project,
+    WorkflowColumn,
+    Task,
+}
+
+impl Kind {
+    fn table(self) -> &'static str {
+        match self {
+            Kind::Folder => "folders",
+            Kind::Project => "projects",
+            Kind::WorkflowColumn => "workflow_columns",
+            Kind::Task => "tasks",
+        }
+    }
+
+    // Child tables keyed by `task_id`, with the column that orders them
+    fn children(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Kind::Task => &[("subtasks", "id"), ("tags", "tag")],
+            _ => &[],
+        }
+    }
+}
+
+pub(super) type Values = BTreeMap<String, serde_json::Value>;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(super) struct Snapshot {
+    pub(super) row: Values,
+    #[serde(default)]
+    pub(super) children: BTreeMap<String, Vec<Values>>,
+}
+
+// One entity's state on either side of an operation; `None` means the row
+// did not exist
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct Change {
+    kind: Kind,
+    // Integer ids as decimal text
+    id: String,
+    before: Option<Snapshot>,
+    after: Option<Snapshot>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Operation {
+    pub id: i64,
+    pub label: String,
+    pub created_at: Timestamp,
+}
+
+// What Ctrl+Z and Ctrl+Shift+Z would do right now
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct UndoState {
+    pub undo: Option<Operation>,
+    pub redo: Option<Operation>,
+}
+
+fn to_json(value: Value) -> serde_json::Value {
+    match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Integer(i) => i.into(),
+        Value::Real(f) => f.into(),
+        Value::Text(s) => s.into(),
+        Value::Blob(b) => b.into(),
+    }
+}
+
+fn to_sql(value: &serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        other => Value::Text(other.to_string()),
+    }
+}
+
+fn json_error(e: serde_json::Error) -> rusqlite::Error {
+    rusqlite::Error::ToSqlConversionFailure(Box::new(e))
+}
+
+fn conflict(message: String) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT), Some(message))
+}
+
+fn is_conflict(e: &rusqlite::Error) -> bool {
+    matches!(e, rusqlite::Error::SqliteFailure(failure, _) if failure.code == ErrorCode::ConstraintViolation)
+}
+
+impl Database {
+    // Runs `f` in a transaction and records what it changed under `label`.
+    // `targets` is asked once before `f` runs (with `None`) and once after
+    // (with its result), so ids that only exist afterwards can be named.
+    // Recording drops anything that could have been redone.
+    pub fn record<T, E>(
+        &self,
+        label: &str,
+        targets: impl Fn(Option<&T>) -> Vec<Target>,
+        f: impl FnOnce(&Database) -> std::result::Result<T, E>,
+    ) -> std::result::Result<T, E>
+    where
+        E: From<rusqlite::Error>,
+    {
+        self.transaction(|db| {
+            let mut keys = db.expand(&targets(None))?;
+            let before = db.snapshots(&keys)?;
+            let value = f(db)?;
+            keys.extend(db.expand(&targets(Some(&value)))?);
+
+            let mut changes = Vec::new();
+            for (kind, id) in keys {
+                let before = before.get(&(kind, id.clone())).cloned().flatten();
+                let after = db.snapshot(kind, &id)?;
+                if before != after {
+                    changes.push(Change { kind, id, before, after });
+                }
+            }
+            if !changes.is_empty() {
+                db.push_operation(label, &changes)?;
+                db.log_task_changes(label, &changes, false)?;
+            }
+            Ok(value)
+        })
+    }
+
+    // For writes that aren't user operations, such as purging the trash:
+    // runs `f` in a transaction and logs what it changed about `task_ids`
+    // to the task history, without making it undoable
+    pub(super) fn with_history<T>(
+        &self,
+        source: &str,
+        task_ids: &[String],
+        f: impl FnOnce(&Database) -> Result<T>,
+    ) -> Result<T> {
+        self.transaction(|db| {
+            let before = task_ids
+                .iter()
+                .map(|id| db.snapshot(Kind::Task, id))
+                .collect::<Result<Vec<_>>>()?;
+            let value = f(db)?;
+            for (id, before) in task_ids.iter().zip(before) {
+                let after = db.snapshot(Kind::Task, id)?;
+                if before != after {
+                    db.log_task_change(source, id, before.as_ref(), after.as_ref())?;
+                }
+            }
+            Ok(value)
+        })
+    }
+
+    // Reverts the latest operation. One whose rows have been changed some
+    // other way since, or that can't be written back without breaking a
+    // constraint, can't be undone cleanly; it is dropped and reported as a
+    // conflict.
+    pub fn undo(&self) -> Result<Option<Operation>> {
+        self.step(true)
+    }
+
+    // Reapplies the most recently undone operation
+    pub fn redo(&self) -> Result<Option<Operation>> {
+        self.step(false)
+    }
+
+    pub fn get_undo_state(&self) -> Result<UndoState> {
+        Ok(UndoState {
+            undo: self.next_operation(true)?.map(|(operation, _)| operation),
+            redo: self.next_operation(false)?.map(|(operation, _)| operation),
+        })
+    }
+
+    fn step(&self, undo: bool) -> Result<Option<Operation>> {
+        // The outer transaction commits dropping an operation that failed;
+        // the inner one rolls back whatever it had written by then
+        self.transaction(|db| {
+            let Some((operation, changes)) = db.next_operation(undo)? else {
+                return Ok(Ok(None));
+            };
+            let applied = db.transaction(|db| {
+                for change in &changes {
+                    let expected = if undo { &change.after } else { &change.before };
+                    if db.snapshot(change.kind, &change.id)? != *expected {
+                        return Err(conflict(format!("{} {} has changed", change.kind.table(), change.id)));
+                    }
+                }
+                db.write_back(
+                    changes
+                        .iter()
+                        .map(|change| (change, if undo { &change.before } else { &change.after }))
+                        .collect(),
+                )?;
+                let source = format!("{}: {}", if undo { "Undo" } else { "Redo" }, operation.label);
+                db.log_task_changes(&source, &changes, undo)?;
+                db.conn.execute(
+                    "UPDATE operations SET undone = ?1 WHERE id = ?2",
+                    params![undo, operation.id],
+                )?;
+                Ok(())
+            });
+            match applied {
+                Ok(()) => Ok(Ok(Some(operation))),
+                Err(e) if is_conflict(&e) => {
+                    log::warn!("Dropping operation '{}': {}", operation.label, e);
+                    // Later undone operations build on this one, so a stale
+                    // redo takes the whole redo history with it
+                    if undo {
+                        db.conn.execute("DELETE FROM operations WHERE id = ?1", [operation.id])?;
+                    } else {
+                        db.conn.execute("DELETE FROM operations WHERE undone = 1", [])?;
+                    }
+                    Ok(Err(conflict(format!(
+                        "'{}' can't be {} because its items have changed since",
+                        operation.label,
+                        if undo { "undone" } else { "redone" }
+                    ))))
+                }
+                Err(e) => Err(e),
+            }
+        })?
+    }
+
+    // Adds the task changes to the task history, backwards when `reverse`
+    fn log_task_changes(&self, source: &str, changes: &[Change], reverse: bool) -> Result<()> {
+        for change in changes.iter().filter(|change| change.kind == Kind::Task) {
+            let (from, to) = if reverse {
+                (&change.after, &change.before)
+            } else {
+                (&change.before, &change.after)
+            };
+            self.log_task_change(source, &change.id, from.as_ref(), to.as_ref())?;
+        }
+        Ok(())
+    }
+
+    // The operation undo (or redo) would apply next, with its changes
+    fn next_operation(&self, undo: bool) -> Result<Option<(Operation, Vec<Change>)>> {
+        let sql = if undo {
+            "SELECT id, label, created_at, changes FROM operations WHERE undone = 0 ORDER BY id DESC LIMIT 1"
+        } else {
+            "SELECT id, label, created_at, changes FROM operations WHERE undone = 1 ORDER BY id LIMIT 1"
+        };
+        let row = self.conn
+            .query_row(sql, [], |row| {
+                Ok((
+                    Operation { id: row.get(0)?, label: row.get(1)?, created_at: row.get(2)? },
+                    row.get::<_, String>(3)?,
+                ))
+            })
+            .optional()?;
+        row.map(|(operation, changes)| {
+            let changes = serde_json::from_str(&changes).map_err(json_error)?;
+            Ok((operation, changes))
+        })
+        .transpose()
+    }
+
+    fn push_operation(&self, label: &str, changes: &[Change]) -> Result<()> {
+        let changes = serde_json::to_string(changes).map_err(json_error)?;
+        self.conn.execute("DELETE FROM operations WHERE undone = 1", [])?;
+        self.conn.execute(
+            "INSERT INTO operations (label, changes, created_at) VALUES (?1, ?2, ?3)",
+            params![label, changes, Timestamp::now()],
+        )?;
+        self.conn.execute(
+            "DELETE FROM operations WHERE id NOT IN (SELECT id FROM operations ORDER BY id DESC LIMIT ?1)",
+            [MAX_OPERATIONS],
+        )?;
+        Ok(())
+    }
+
+    // Writes snapshots back: rows that should exist are upserted parents
+    // first, rows that shouldn't are deleted children first
+    fn write_back(&self, mut states: Vec<(&Change, &Option<Snapshot>)>) -> Result<()> {
+        states.sort_by_key(|(change, _)| change.kind);
+        for (change, state) in &states {
+            if let Some(snapshot) = state {
+                self.restore_snapshot(change.kind, &change.id, snapshot)?;
+            }
+        }
+        for (change, state) in states.iter().rev() {
+            if state.is_none() {
+                self.conn.execute(&format!("DELETE FROM {} WHERE id = ?1", change.kind.table()), [&change.id])?;
+            }
+        }
+        // Derived state (completion history, reminder times) follows the
+        // restored tasks
+        for (change, state) in &states {
+            if change.kind == Kind::Task && state.is_some() {
+                self.reschedule_relative_reminders(&change.id)?;
+                self.sync_recurrence(&change.id)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn restore_snapshot(&self, kind: Kind, id: &str, snapshot: &Snapshot) -> Result<()> {
+        insert_values(self, kind.table(), &snapshot.row, true)?;
+        for (table, _) in kind.children() {
+            self.conn.execute(&format!("DELETE FROM {} WHERE task_id = ?1", table), [id])?;
+            for row in snapshot.children.get(*table).into_iter().flatten() {
+                insert_values(self, table, row, false)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Entity keys behind `targets`, including trashed rows
+    fn expand(&self, targets: &[Target]) -> Result<BTreeSet<(Kind, String)>> {
+        let mut keys = BTreeSet::new();
+        let mut add = |kind: Kind, sql: &str, id: &dyn rusqlite::ToSql| -> Result<()> {
+            let mut stmt = self.conn.prepare(sql)?;
+            let ids = stmt.query_map([id], |row| row.get::<_, Value>(0))?;
+            for found in ids {
+                keys.insert((kind, match found? {
+                    Value::Integer(i) => i.to_string(),
+                    Value::Text(s) => s,
+                    _ => continue,
+                }));
+            }
+            Ok(())
+        };
+        for target in targets {
+            match target {
+                Target::Task(id) => add(
+                    Kind::Task,
+                    "SELECT id FROM tasks
+                     WHERE id = ?1 OR series_id = (SELECT series_id FROM tasks WHERE id = ?1)",
+                    id,
+                )?,
+                Target::Subtask(id) => add(
+                    Kind::Task,
+                    "SELECT task_id FROM subtasks WHERE id = ?1",
+                    id,
+                )?,
+                Target::Project(id) => {
+                    add(Kind::Project, "SELECT id FROM projects WHERE id = ?1", id)?;
+                    add(Kind::Task, "SELECT id FROM tasks WHERE project_id = ?1", id)?;
+                }
+                Target::Folder(id) => {
+                    add(Kind::Folder, "SELECT id FROM folders WHERE id = ?1", id)?;
+                    add(Kind::Project, "SELECT id FROM projects WHERE folder_id = ?1", id)?;
+                    add(
+                        Kind::Task,
+                        "SELECT id FROM tasks WHERE project_id IN (SELECT id FROM projects WHERE folder_id = ?1)",
+                        id,
+                    )?;
+                }
+                Target::Workflow(project_id) => add(
+                    Kind::WorkflowColumn,
+                    "SELECT id FROM workflow_columns WHERE project_id = ?1",
+                    project_id,
+                )?,
+            }
+        }
+        Ok(keys)
+    }
+
+    fn snapshots(&self, keys: &BTreeSet<(Kind, String)>) -> Result<BTreeMap<(Kind, String), Option<Snapshot>>> {
+        keys.iter()
+            .map(|(kind, id)| Ok(((*kind, id.clone()), self.snapshot(*kind, id)?)))
+            .collect()
+    }
+
+    fn snapshot(&self, kind: Kind, id: &str) -> Result<Option<Snapshot>> {
+        let Some(row) = select_values(self, &format!("SELECT * FROM {} WHERE id = ?1", kind.table()), id)?
+            .into_iter()
+            .next()
+        else {
+            return Ok(None);
+        };
+        let mut children = BTreeMap::new();
+        for (table, order) in kind.children() {
+            let rows = select_values(
+                self,
+                &format!("SELECT * FROM {} WHERE task_id = ?1 ORDER BY {}", table, order),
+                id,
+            )?;
+            children.insert(table.to_string(), rows);
+        }
+        Ok(Some(Snapshot { row, children }))
+    }
+}
+
+fn select_values(db: &Database, sql: &str, id: &str) -> Result<Vec<Values>> {
+    let mut stmt = db.conn.prepare(sql)?;
+    let columns: Vec<String> = stmt.column_names().into_iter().map(str::to_string).collect();
+    let rows = stmt.query_map([id], |row| {
+        let mut values = Values::new();
+        for (i, column) in columns.iter().enumerate() {
+            values.insert(column.clone(), to_json(row.get(i)?));
+        }
+        Ok(values)
+    })?;
+    rows.collect()
+}
+
+// Inserts a snapshot row; with `upsert` an existing row with the same id is
+// overwritten in place so rows referencing it stay valid
+fn insert_values(db: &Database, table: &str, values: &Values, upsert: bool) -> Result<()> {
+    let columns: Vec<&str> = values.keys().map(String::as_str).collect();
+    let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+    let mut sql = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        table,
+        columns.join(", "),
+        placeholders.join(", ")
+    );
+    if upsert {
+        let updates: Vec<String> = columns.iter().map(|c| format!("{} = excluded.{}", c, c)).collect();
+        sql.push_str(&format!(" ON CONFLICT(id) DO UPDATE SET {}", updates.join(", ")));
+    }
+    let params: Vec<Value> = values.values().map(to_sql).collect();
+    db.conn.execute(&sql, rusqlite::params_from_iter(params))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{Task, TaskStatus, WorkflowColumnInput};
+
+    fn rename(db: &Database, id: &str, title: &str) -> Result<usize> {
+        db.record("Rename task", |_| vec![Target::Task(id.to_string())], |db| {
+            db.conn.execute("UPDATE tasks SET title = ?1 WHERE id = ?2", params![title, id])
+        })
+    }
+
+    fn title(db: &Database, id: &str) -> Option<String> {
+        db.conn.query_row("SELECT title FROM tasks WHERE id = ?1", [id], |row| row.get(0)).optional().unwrap()
+    }
+
+    fn labels(state: &UndoState) -> (Option<&str>, Option<&str>) {
+        (state.undo.as_ref().map(|op| op.label.as_str()), state.redo.as_ref().map(|op| op.label.as_str()))
+    }
+
+    fn is_conflict_error(result: Result<Option<Operation>>) -> bool {
+        matches!(result, Err(e) if is_conflict(&e))
+    }
+
+    #[test]
+    fn undo_and_redo_round_trip() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(db.undo().unwrap().is_none());
+        db.record("Create task", |_| vec![Target::Task("t1".to_string())], |db| {
+            db.create_task(&Task::sample("t1"), &["one".into()], &["a".into()])
+        })
+        .unwrap();
+        rename(&db, "t1", "renamed").unwrap();
+        db.record("Delete task", |_| vec![Target::Task("t1".to_string())], |db| db.delete_task("t1")).unwrap();
+        assert_eq!(labels(&db.get_undo_state().unwrap()), (Some("Delete task"), None));
+
+        assert_eq!(db.undo().unwrap().unwrap().label, "Delete task");
+        assert!(db.get_task("t1").unwrap().is_some());
+        assert_eq!(db.undo().unwrap().unwrap().label, "Rename task");
+        assert_eq!(title(&db, "t1").as_deref(), Some("t1"));
+        assert_eq!(labels(&db.get_undo_state().unwrap()), (Some("Create task"), Some("Rename task")));
+        assert_eq!(db.undo().unwrap().unwrap().label, "Create task");
+        assert_eq!(title(&db, "t1"), None);
+        assert!(db.undo().unwrap().is_none());
+
+        assert_eq!(db.redo().unwrap().unwrap().label, "Create task");
+        let task = db.get_task("t1").unwrap().unwrap();
+        assert_eq!(task.subtasks[0].text, "one");
+        assert_eq!(task.tags, ["a"]);
+        assert_eq!(db.redo().unwrap().unwrap().label, "Rename task");
+        assert_eq!(db.redo().unwrap().unwrap().label, "Delete task");
+        assert!(db.get_task("t1").unwrap().is_none());
+        assert_eq!(title(&db, "t1").as_deref(), Some("renamed"));
+        assert!(db.redo().unwrap().is_none());
+
+        // A new change discards what could have been redone
+        db.undo().unwrap();
+        rename(&db, "t1", "again").unwrap();
+        assert_eq!(labels(&db.get_undo_state().unwrap()), (Some("Rename task"), None));
+    }
+
+    #[test]
+    fn stale_operations_are_dropped() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_task(&Task::sample("t1"), &[], &[]).unwrap();
+        rename(&db, "t1", "first").unwrap();
+        rename(&db, "t1", "second").unwrap();
+
+        // Undone twice, then changed outside the undo history: neither
+        // operation can be redone
+        db.undo().unwrap();
+        db.undo().unwrap();
+        db.conn.execute("UPDATE tasks SET title = 'elsewhere' WHERE id = 't1'", []).unwrap();
+        assert!(is_conflict_error(db.redo()));
+        assert_eq!(labels(&db.get_undo_state().unwrap()), (None, None));
+        assert_eq!(title(&db, "t1").as_deref(), Some("elsewhere"));
+
+        rename(&db, "t1", "third").unwrap();
+        rename(&db, "t1", "fourth").unwrap();
+        db.conn.execute("UPDATE tasks SET title = 'elsewhere' WHERE id = 't1'", []).unwrap();
+        assert!(is_conflict_error(db.undo()));
+        assert_eq!(title(&db, "t1").as_deref(), Some("elsewhere"));
+        // Only the stale operation is dropped
+        let state = db.get_undo_state().unwrap();
+        assert_eq!(labels(&state), (Some("Rename task"), None));
+        assert_eq!(db.conn.query_row("SELECT COUNT(*) FROM operations", [], |row| row.get::<_, i64>(0)).unwrap(), 1);
+    }
+
+    #[test]
+    fn operations_that_break_a_constraint_are_dropped() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_task(&Task { project_id: Some(1), ..Task::sample("t1") }, &[], &[]).unwrap();
+        let column = |id, name: &str, status| WorkflowColumnInput { id, name: name.to_string(), status, wip_limit: None };
+        let columns = db
+            .set_project_workflow(1, &[column(None, "Doing", TaskStatus::Todo), column(None, "Done", TaskStatus::Done)])
+            .unwrap();
+        db.move_task_to_column("t1", columns[0].id).unwrap();
+        db.create_task(&Task::sample("t2"), &[], &[]).unwrap();
+        rename(&db, "t2", "renamed").unwrap();
+
+        // Without the columns in the operation, undo would point the task
+        // back at a column that no longer exists
+        db.record("Edit workflow", |_| vec![Target::Task("t1".to_string())], |db| {
+            db.set_project_workflow(1, &[column(Some(columns[1].id), "Done", TaskStatus::Done)])
+        })
+        .unwrap();
+        assert!(is_conflict_error(db.undo()));
+
+        assert_eq!(db.get_task("t1").unwrap().unwrap().task.column_id, None);
+        assert_eq!(labels(&db.get_undo_state().unwrap()), (Some("Rename task"), None));
+        assert_eq!(db.undo().unwrap().unwrap().label, "Rename task");
+        assert_eq!(title(&db, "t2").as_deref(), Some("t2"));
+    }
+
+    #[test]
+    fn only_the_latest_operations_are_kept() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_task(&Task::sample("t1"), &[], &[]).unwrap();
+        for i in 0..MAX_OPERATIONS + 5 {
+            rename(&db, "t1", &format!("title {}", i)).unwrap();
+        }
+
+        let count: i64 = db.conn.query_row("SELECT COUNT(*) FROM operations", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, MAX_OPERATIONS);
+
+        // Undoing everything stops at the oldest operation still kept
+        let mut undone = 0;
+        while db.undo().unwrap().is_some() {
+            undone += 1;
+        }
+        assert_eq!(undone, MAX_OPERATIONS);
+        assert_eq!(title(&db, "t1").as_deref(), Some("title 4"));
+    }
+}
@@ -0,0 +1,359 @@
+use rusqlite::{OptionalExtension, Result, Row, params};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use chrono::Utc;
+
+use super::{Database, TaskStatus};
+
+// One column of a project's Kanban board. `status` is the workflow category
+// the column belongs to, so moving a card into a "Review" column still marks
+// the task as in-progress.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkflowColumn {
+    pub id: i32,
+    pub project_id: i32,
+    pub name: String,
+    pub position: i32,
+    pub status: TaskStatus,
+    pub wip_limit: Option<i64>,
+}
+
+// Column as sent by the frontend when saving a workflow. Columns without an
+// `id` are created; existing columns that are left out are removed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkflowColumnInput {
+    pub id: Option<i32>,
+    pub name: String,
+    pub status: TaskStatus,
+    pub wip_limit: Option<i64>,
+}
+
+// Why a workflow change was refused. Commands report it as an `AppError`.
+#[derive(Debug)]
+pub enum WorkflowError {
+    TaskNotFound { task_id: String },
+    ColumnNotFound { column_id: i32 },
+    ColumnNotInProject { column_id: i32, project_id: Option<i32> },
+    WipLimitReached { column_id: i32, column_name: String, wip_limit: i64 },
+    InvalidWorkflow { message: String },
+    Database(rusqlite::Error),
+}
+
+impl fmt::Display for WorkflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkflowError::TaskNotFound { task_id } => write!(f, "Task {} not found", task_id),
+            WorkflowError::ColumnNotFound { column_id } => write!(f, "Column {} not found", column_id),
+            WorkflowError::ColumnNotInProject { column_id, project_id: Some(project_id) } => {
+                write!(f, "Column {} does not belong to project {}", column_id, project_id)
+            }
+            WorkflowError::ColumnNotInProject { column_id, project_id: None } => {
+                write!(f, "Column {} can't hold a task without a project", column_id)
+            }
+            WorkflowError::WipLimitReached { column_name, wip_limit, .. } => {
+                write!(f, "Column '{}' is at its WIP limit of {}", column_name, wip_limit)
+            }
+            WorkflowError::InvalidWorkflow { message } => write!(f, "Invalid workflow: {}", message),
+            WorkflowError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for WorkflowError {
+    fn from(e: rusqlite::Error) -> Self {
+        WorkflowError::Database(e)
+    }
+}
+
+fn column_from_row(row: &Row) -> Result<WorkflowColumn> {
+    Ok(WorkflowColumn {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        name: row.get(2)?,
+        position: row.get(3)?,
+        status: row.get(4)?,
+        wip_limit: row.get(5)?,
+    })
+}
+
+impl Database {
+    // An empty list means the project uses the default todo / in-progress /
+    // done board.
+    pub fn get_project_workflow(&self, project_id: i32) -> Result<Vec<WorkflowColumn>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, project_id, name, position, status, wip_limit
+             FROM workflow_columns WHERE project_id = ?1 ORDER BY position"
+        )?;
+        let columns = stmt.query_map([project_id], column_from_row)?;
+        columns.collect()
+    }
+
+    // Tasks placed in one of the project's columns, trashed ones included
+    pub fn get_board_task_ids(&self, project_id: i32) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT t.id FROM tasks t JOIN workflow_columns c ON c.id = t.column_id WHERE c.project_id = ?1"
+        )?;
+        let ids = stmt.query_map([project_id], |row| row.get(0))?;
+        ids.collect()
+    }
+
+    pub fn set_project_workflow(
+        &self,
+        project_id: i32,
+        columns: &[WorkflowColumnInput],
+    ) -> std::result::Result<Vec<WorkflowColumn>, WorkflowError> {
+        for column in columns {
+            if column.name.trim().is_empty() {
+                return Err(WorkflowError::InvalidWorkflow {
+                    message: "Column names cannot be empty".to_string(),
+                });
+            }
+            if matches!(column.wip_limit, Some(limit) if limit < 1) {
+                return Err(WorkflowError::InvalidWorkflow {
+                    message: format!("WIP limit for '{}' must be at least 1", column.name),
+                });
+            }
+        }
+        if !columns.is_empty() && !columns.iter().any(|c| c.status.is_terminal()) {
+            return Err(WorkflowError::InvalidWorkflow {
+                message: "A workflow needs at least one done column".to_string(),
+            });
+        }
+
+        self.transaction(|db| {
+            let existing = db.get_project_workflow(project_id)?;
+            // Columns dropped from the list are removed; their tasks fall back to
+            // the first column matching their status (column_id is set to NULL).
+            for old in &existing {
+                if !columns.iter().any(|c| c.id == Some(old.id)) {
+                    db.conn.execute("DELETE FROM workflow_columns WHERE id = ?1", params![old.id])?;
+                }
+            }
+
+            for (position, column) in columns.iter().enumerate() {
+                let position = position as i32;
+                match column.id {
+                    Some(id) => {
+                        if !existing.iter().any(|c| c.id == id) {
+                            return Err(WorkflowError::ColumnNotInProject {
+                                column_id: id,
+                                project_id: Some(project_id),
+                            });
+                        }
+                        db.conn.execute(
+                            "UPDATE workflow_columns SET name = ?1, position = ?2, status = ?3, wip_limit = ?4 WHERE id = ?5",
+                            params![column.name.trim(), position, column.status, column.wip_limit, id],
+                        )?;
+                        db.recategorise_column_tasks(id, column.status)?;
+                    }
+                    None => {
+                        db.conn.execute(
+                            "INSERT INTO workflow_columns (project_id, name, position, status, wip_limit) VALUES (?1, ?2, ?3, ?4, ?5)",
+                            params![project_id, column.name.trim(), position, column.status, column.wip_limit],
+                        )?;
+                    }
+                }
+            }
+
+            Ok(db.get_project_workflow(project_id)?)
+        })
+    }
+
+    // Keeps the cards of a column whose category changed consistent with it,
+    // the same as if each had been given the new status. Trashed cards are
+    // taken off the board instead, and placed by status if restored.
+    fn recategorise_column_tasks(&self, column_id: i32, status: TaskStatus) -> Result<()> {
+        let task_ids = {
+            let mut stmt = self.conn.prepare(
+                "SELECT id FROM tasks WHERE column_id = ?1 AND status != ?2 AND deleted_at IS NULL"
+            )?;
+            stmt.query_map(params![column_id, status], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>>>()?
+        };
+        for task_id in task_ids {
+            self.update_task_status(&task_id, status)?;
+        }
+        self.conn.execute(
+            "UPDATE tasks SET column_id = NULL WHERE column_id = ?1 AND status != ?2 AND deleted_at IS NOT NULL",
+            params![column_id, status],
+        )?;
+        Ok(())
+    }
+
+    // Moves a task into a workflow column, enforcing the column's WIP limit.
+    // The task's status and completion follow the column's category.
+    pub fn move_task_to_column(
+        &self,
+        task_id: &str,
+        column_id: i32,
+    ) -> std::result::Result<(), WorkflowError> {
+        self.transaction(|db| {
+            let task: Option<(Option<i32>, Option<i32>)> = db.conn
+                .query_row(
+                    "SELECT project_id, column_id FROM tasks WHERE id = ?1 AND deleted_at IS NULL",
+                    [task_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+            let (project_id, current_column) = task.ok_or_else(|| WorkflowError::TaskNotFound {
+                task_id: task_id.to_string(),
+            })?;
+
+            if current_column == Some(column_id) {
+                return Ok(());
+            }
+
+            let column = db.conn
+                .query_row(
+                    "SELECT id, project_id, name, position, status, wip_limit FROM workflow_columns WHERE id = ?1",
+                    [column_id],
+                    column_from_row,
+                )
+                .optional()?
+                .ok_or(WorkflowError::ColumnNotFound { column_id })?;
+
+            if project_id != Some(column.project_id) {
+                return Err(WorkflowError::ColumnNotInProject { column_id, project_id });
+            }
+
+            if let Some(wip_limit) = column.wip_limit {
+                let in_column: i64 = db.conn.query_row(
+                    "SELECT COUNT(*) FROM tasks WHERE column_id = ?1 AND deleted_at IS NULL",
+                    [column_id],
+                    |row| row.get(0),
+                )?;
+                if in_column >= wip_limit {
+                    return Err(WorkflowError::WipLimitReached {
+                        column_id,
+                        column_name: column.name,
+                        wip_limit,
+                    });
+                }
+            }
+
+            let now = Utc::now().to_rfc3339();
+            db.conn.execute(
+                "UPDATE tasks SET column_id = ?1, status = ?2, completed = ?3, updated_at = ?4 WHERE id = ?5",
+                params![column_id, column.status, column.status.is_terminal(), now, task_id],
+            )?;
+            db.sync_completed_at(task_id)?;
+            db.sync_recurrence(task_id)?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{Target, Task, Timestamp};
+
+    fn column(id: Option<i32>, name: &str, status: TaskStatus) -> WorkflowColumnInput {
+        WorkflowColumnInput { id, name: name.to_string(), status, wip_limit: None }
+    }
+
+    #[test]
+    fn recategorised_column_updates_its_tasks_like_a_status_change() {
+        let db = Database::open_in_memory().unwrap();
+        let created = Timestamp::from(Utc::now() - chrono::Duration::days(1));
+        let task = Task {
+            due_date: Some("2026-10-12".parse().unwrap()),
+            project_id: Some(1),
+            recurrence: Some(serde_json::from_str(r#"{"frequency":"daily","month_day":null,"until":null,"count":null}"#).unwrap()),
+            series_id: Some("s1".to_string()),
+            created_at: created,
+            updated_at: created,
+            ..Task::sample("t1")
+        };
+        db.create_task(&task, &[], &[]).unwrap();
+
+        let columns = db
+            .set_project_workflow(1, &[column(None, "Review", TaskStatus::Todo), column(None, "Done", TaskStatus::Done)])
+            .unwrap();
+        db.move_task_to_column("t1", columns[0].id).unwrap();
+        db.conn.execute("UPDATE tasks SET updated_at = ?1 WHERE id = 't1'", [created]).unwrap();
+
+        db.set_project_workflow(1, &[
+            column(Some(columns[0].id), "Review", TaskStatus::Done),
+            column(Some(columns[1].id), "Done", TaskStatus::Done),
+        ])
+        .unwrap();
+
+        let after = db.get_task("t1").unwrap().unwrap().task;
+        assert!(after.completed);
+        assert_eq!(after.status, TaskStatus::Done);
+        assert_eq!(after.column_id, Some(columns[0].id));
+        assert!(after.completed_at.is_some());
+        assert!(after.updated_at > created);
+        // Completing the occurrence spawned the next one
+        assert_eq!(db.get_series_history("s1").unwrap().completions.len(), 1);
+        let open: i64 = db.conn
+            .query_row("SELECT COUNT(*) FROM tasks WHERE series_id = 's1' AND completed = 0", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(open, 1);
+    }
+
+    #[test]
+    fn recorded_workflow_change_can_be_undone() {
+        let db = Database::open_in_memory().unwrap();
+        let task = Task { project_id: Some(1), ..Task::sample("t1") };
+        db.create_task(&task, &[], &[]).unwrap();
+        let columns = db
+            .set_project_workflow(1, &[column(None, "Review", TaskStatus::Todo), column(None, "Done", TaskStatus::Done)])
+            .unwrap();
+        db.move_task_to_column("t1", columns[0].id).unwrap();
+
+        let task_ids = db.get_board_task_ids(1).unwrap();
+        assert_eq!(task_ids, ["t1"]);
+        db.record(
+            "Edit workflow",
+            |_| std::iter::once(Target::Workflow(1)).chain(task_ids.iter().cloned().map(Target::Task)).collect(),
+            |db| db.set_project_workflow(1, &[column(Some(columns[0].id), "Review", TaskStatus::Done)]),
+        )
+        .unwrap();
+        assert!(db.get_task("t1").unwrap().unwrap().task.completed);
+        let history = db.get_task_history("t1").unwrap();
+        assert!(history.iter().any(|entry| entry.field == "status" && entry.source == "Edit workflow"));
+
+        let undone = db.undo().unwrap().unwrap();
+        assert_eq!(undone.label, "Edit workflow");
+        let task = db.get_task("t1").unwrap().unwrap().task;
+        assert!(!task.completed);
+        assert_eq!(task.status, TaskStatus::Todo);
+    }
+
+    #[test]
+    fn undoing_a_column_removal_puts_its_cards_back() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_task(&Task { project_id: Some(1), ..Task::sample("t1") }, &[], &[]).unwrap();
+        let columns = db
+            .set_project_workflow(1, &[column(None, "Doing", TaskStatus::Todo), column(None, "Done", TaskStatus::Done)])
+            .unwrap();
+        db.move_task_to_column("t1", columns[0].id).unwrap();
+
+        let edit = |columns: Vec<WorkflowColumnInput>| {
+            let task_ids = db.get_board_task_ids(1).unwrap();
+            db.record(
+                "Edit workflow",
+                |_| std::iter::once(Target::Workflow(1)).chain(task_ids.iter().cloned().map(Target::Task)).collect(),
+                |db| db.set_project_workflow(1, &columns),
+            )
+            .unwrap()
+        };
+        edit(vec![column(Some(columns[1].id), "Done", TaskStatus::Done), column(None, "Later", TaskStatus::Todo)]);
+        assert_eq!(db.get_task("t1").unwrap().unwrap().task.column_id, None);
+
+        db.undo().unwrap().unwrap();
+        let restored = db.get_project_workflow(1).unwrap();
+        assert_eq!(
+            restored.iter().map(|c| (c.id, c.name.as_str(), c.position)).collect::<Vec<_>>(),
+            [(columns[0].id, "Doing", 0), (columns[1].id, "Done", 1)]
+        );
+        assert_eq!(db.get_task("t1").unwrap().unwrap().task.column_id, Some(columns[0].id));
+
+        db.redo().unwrap().unwrap();
+        let redone = db.get_project_workflow(1).unwrap();
+        assert_eq!(redone.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["Done", "Later"]);
+        assert_eq!(db.get_task("t1").unwrap().unwrap().task.column_id, None);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::database::{PoolError, QueryError, WorkflowError};
+use rusqlite::ErrorCode;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// Error returned by commands. Serialized as `{ "kind": ..., "message": ... }`
+// plus any details, so the frontend can branch on `kind` and show `message`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AppError {
+    // `entity`/`id` are unset when the database only reported a missing row
+    NotFound { message: String, entity: Option<String>, id: Option<String> },
+    // `field` names the offending request field, when there is one; `span`
+    // is the part of a parsed string at fault, e.g. a query term
+    Validation { message: String, field: Option<String>, span: Option<Span> },
+    // A constraint was violated, e.g. a duplicate name or a dangling reference.
+    // `details` is set when the frontend can tell the user more.
+    Conflict { message: String, details: Option<ConflictDetails> },
+    // Another connection held a lock for longer than the busy timeout; retrying
+    // may succeed
+    Busy { message: String },
+    Storage { message: String },
+    Internal { message: String },
+}
+
+// Character offsets into the offending string, `end` exclusive
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum ConflictDetails {
+    WipLimitReached { column_id: i32, column_name: String, wip_limit: i64 },
+}
+
+impl AppError {
+    pub fn not_found(entity: &str, id: impl fmt::Display) -> Self {
+        let mut name = entity.to_string();
+        if let Some(first) = name.get_mut(..1) {
+            first.make_ascii_uppercase();
+        }
+        AppError::NotFound {
+            message: format!("{} {} not found", name, id),
+            entity: Some(entity.to_string()),
+            id: Some(id.to_string()),
+        }
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        AppError::Validation { message: message.into(), field: None, span: None }
+    }
+
+    pub fn invalid_field(field: &str, message: impl Into<String>) -> Self {
+        AppError::Validation { message: message.into(), field: Some(field.to_string()), span: None }
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        AppError::Conflict { message: message.into(), details: None }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::NotFound { message, .. }
+            | AppError::Validation { message, .. }
+            | AppError::Conflict { message, .. }
+            | AppError::Busy { message }
+            | AppError::Storage { message }
+            | AppError::Internal { message } => message,
+        }
+    }
+
+    // Names the record behind a bare "not found" from the database
+    pub fn about(self, entity: &str, id: impl fmt::Display) -> Self {
+        match self {
+            AppError::NotFound { entity: None, .. } => AppError::not_found(entity, id),
+            other => other,
+        }
+    }
+
+    // Prefixes what was being done. Not-found and validation messages are
+    // already meant for the user and are left alone.
+    pub fn context(self, context: &str) -> Self {
+        let prefix = |message: String| format!("{}: {}", context, message);
+        match self {
+            AppError::Conflict { message, details } => AppError::Conflict { message: prefix(message), details },
+            AppError::Busy { message } => AppError::Busy { message: prefix(message) },
+            AppError::Storage { message } => AppError::Storage { message: prefix(message) },
+            AppError::Internal { message } => AppError::Internal { message: prefix(message) },
+            other => other,
+        }
+    }
+}
+
+// `map_err` adapter replacing the old `format!("Failed to …: {}", e)`
+pub fn failed_to<E: Into<AppError>>(action: &str) -> impl Fn(E) -> AppError + '_ {
+    move |e| e.into().context(&format!("Failed to {}", action))
+}
+
+// Parses one request field into a domain type, reporting a failure against
+// that field
+pub fn parse_field<T: FromStr<Err = String>>(field: &str, value: &str) -> Result<T, AppError> {
+    value.parse().map_err(|e| AppError::invalid_field(field, e))
+}
+
+// Like `parse_field`, but a missing or blank value is `None`
+pub fn parse_optional_field<T: FromStr<Err = String>>(
+    field: &str,
+    value: Option<&str>,
+) -> Result<Option<T>, AppError> {
+    value
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| parse_field(field, value))
+        .transpose()
+}
+
+// Rejects blank text such as an empty title
+pub fn require_text(field: &str, value: &str) -> Result<(), AppError> {
+    if value.trim().is_empty() {
+        let mut name = field.replace('_', " ");
+        if let Some(first) = name.get_mut(..1) {
+            first.make_ascii_uppercase();
+        }
+        return Err(AppError::invalid_field(field, format!("{} must not be empty", name)));
+    }
+    Ok(())
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        let message = e.to_string();
+        match &e {
+            rusqlite::Error::QueryReturnedNoRows => {
+                AppError::NotFound { message: "Not found".to_string(), entity: None, id: None }
+            }
+            rusqlite::Error::SqliteFailure(failure, _) => match failure.code {
+                ErrorCode::ConstraintViolation => AppError::conflict(message),
+                ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => AppError::Busy { message },
+                _ => AppError::Storage { message },
+            },
+            _ => AppError::Storage { message },
+        }
+    }
+}
+
+impl From<PoolError> for AppError {
+    fn from(e: PoolError) -> Self {
+        match e {
+            PoolError::Worker(_) => AppError::Internal { message: e.to_string() },
+        }
+    }
+}
+
+impl From<WorkflowError> for AppError {
+    fn from(e: WorkflowError) -> Self {
+        let message = e.to_string();
+        match e {
+            WorkflowError::TaskNotFound { task_id } => AppError::not_found("task", task_id),
+            WorkflowError::ColumnNotFound { column_id } => AppError::not_found("column", column_id),
+            WorkflowError::ColumnNotInProject { .. } => AppError::invalid_field("column_id", message),
+            WorkflowError::WipLimitReached { column_id, column_name, wip_limit } => AppError::Conflict {
+                message,
+                details: Some(ConflictDetails::WipLimitReached { column_id, column_name, wip_limit }),
+            },
+            WorkflowError::InvalidWorkflow { .. } => AppError::invalid_field("columns", message),
+            WorkflowError::Database(e) => e.into(),
+        }
+    }
+}
+
+impl From<QueryError> for AppError {
+    fn from(e: QueryError) -> Self {
+        let message = e.to_string();
+        match e {
+            QueryError::Parse { start, end, .. } => AppError::Validation {
+                message,
+                field: Some("query".to_string()),
+                span: Some(Span { start, end }),
+            },
+            QueryError::Database(e) => e.into(),
+        }
+    }
+}
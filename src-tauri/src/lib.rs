@@ -2,10 +2,14 @@
 
 mod database;
 mod commands;
+mod error;
+mod logging;
+mod patch;
+mod scheduler;
 
-use database::Database;
+use database::DatabasePool;
 use commands::*;
-use std::sync::Mutex;
+use scheduler::ReminderScheduler;
 
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -14,27 +18,104 @@ fn greet(name: &str) -> String {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Logging comes first so that opening and migrating the database is logged
+    logging::init();
+
     // Initialize database
-    let database = Database::new().expect("Failed to initialize database");
-    let db_state = Mutex::new(database);
+    let db_state = DatabasePool::open().unwrap_or_else(|e| {
+        log::error!("Failed to initialize database: {}", e);
+        panic!("Failed to initialize database: {}", e);
+    });
+    tauri::async_runtime::block_on(logging::load_level(&db_state));
+    log::info!("UltraList {} starting", env!("CARGO_PKG_VERSION"));
+    let scheduler = ReminderScheduler::default();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(db_state)
+        .manage(scheduler.clone())
+        .setup(move |app| {
+            let handle = app.handle().clone();
+            logging::start_streaming(handle.clone());
+            scheduler.start(handle.clone(), scheduler::notifier_from_env(handle.clone()));
+            trash::start_purging(handle);
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Destroyed = event {
+                logging::unsubscribe(window.label());
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             // Task commands
             create_task,
             get_all_tasks,
+            get_task,
+            task_list::list_tasks,
+            search::search_tasks,
+            query::query_tasks,
+            // Saved search commands
+            saved_searches::create_saved_search,
+            saved_searches::get_saved_searches,
+            saved_searches::update_saved_search,
+            saved_searches::delete_saved_search,
+            saved_searches::evaluate_saved_search,
+            saved_searches::get_saved_search_counts,
+            // Template commands
+            templates::create_template,
+            templates::list_templates,
+            templates::delete_template,
+            templates::instantiate_template,
+            project_templates::save_project_as_template,
+            project_templates::list_project_templates,
+            project_templates::delete_project_template,
+            project_templates::instantiate_project_template,
             update_task,
             delete_task,
             toggle_task_completion,
+            set_task_status,
+            recurrence::get_series_history,
+            // Reminder commands
+            reminders::add_reminder,
+            reminders::get_reminders_for_task,
+            reminders::delete_reminder,
+            reminders::snooze_reminder,
+            reminders::dismiss_reminder,
             // Subtask commands
-            toggle_subtask_completion,
+            subtasks::add_subtask,
+            subtasks::rename_subtask,
+            subtasks::delete_subtask,
+            subtasks::reorder_subtasks,
+            subtasks::toggle_subtask,
+            subtasks::toggle_subtask_completion,
             // Project commands
             create_project,
             get_all_projects,
             delete_project,
+            // Workflow commands
+            workflows::get_project_workflow,
+            workflows::set_project_workflow,
+            workflows::move_task_to_column,
+            // Trash commands
+            trash::list_trash,
+            trash::restore_item,
+            trash::empty_trash,
+            trash::get_trash_retention_days,
+            trash::set_trash_retention_days,
+            undo::undo,
+            undo::redo,
+            undo::get_undo_state,
+            history::get_task_history,
+            stats::get_productivity_stats,
+            pomodoro::start_pomodoro_session,
+            pomodoro::finish_pomodoro_session,
+            pomodoro::abort_pomodoro_session,
+            pomodoro::get_active_pomodoro_session,
+            pomodoro::get_pomodoro_settings,
+            pomodoro::save_pomodoro_settings,
+            pomodoro::get_focus_stats,
             // Folder commands
             create_folder,
             get_all_folders,
@@ -51,7 +132,12 @@ pub fn run() {
             get_searchbar_mode,
             parse_natural_language_task,
             // Logging commands
-            get_application_logs
+            logs::get_application_logs,
+            logs::get_log_level,
+            logs::set_log_level,
+            logs::subscribe_logs,
+            logs::unsubscribe_logs,
+            logs::write_frontend_log
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
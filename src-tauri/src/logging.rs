@@ -0,0 +1,350 @@
+use crate::database::DatabasePool;
+use crate::error::AppError;
+use chrono::{DateTime, SecondsFormat, Utc};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::{HashSet, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock, PoisonError};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+// Backend logging through the `log` facade. Every record goes to a JSON-lines
+// file under ~/.ultralist/logs, rotated by size, and to an in-memory ring
+// buffer that the logs window reads through `get_application_logs`. Windows
+// that subscribe also get each new entry pushed as a `LOG_EVENT` event.
+
+// Settings key holding the runtime log level (off, error, warn, info, debug
+// or trace)
+pub const LEVEL_SETTING: &str = "log_level";
+// Event carrying a single `LogEntry` to subscribed windows
+pub const LOG_EVENT: &str = "log-entry";
+// Target prefix of entries forwarded by the frontend
+pub const FRONTEND_TARGET: &str = "react";
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::Info;
+
+const BUFFER_CAPACITY: usize = 5000;
+const MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+// ultralist.log plus ultralist.1.log .. ultralist.4.log
+const MAX_FILES: usize = 5;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LogEntry {
+    // Increases by one per entry, so a poller can ask for what is new
+    pub id: u64,
+    pub timestamp: String,
+    pub level: String,
+    pub message: String,
+    pub target: Option<String>,
+    #[serde(skip)]
+    time: DateTime<Utc>,
+    #[serde(skip)]
+    severity: Level,
+}
+
+// Every set criterion must match. `level` is the least severe level to
+// include, `target` matches anywhere in the target, and `since`/`until` are
+// RFC 3339 times. At most `limit` entries are returned, the newest ones.
+#[derive(Debug, Deserialize, Default)]
+pub struct LogFilter {
+    pub level: Option<String>,
+    pub target: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub after_id: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+struct LogFile {
+    dir: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let file = OpenOptions::new().create(true).append(true).open(Self::path(dir, 0))?;
+        let size = file.metadata()?.len();
+        Ok(LogFile { dir: dir.to_path_buf(), file, size })
+    }
+
+    fn path(dir: &Path, index: usize) -> PathBuf {
+        if index == 0 {
+            dir.join("ultralist.log")
+        } else {
+            dir.join(format!("ultralist.{}.log", index))
+        }
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 + 1 > MAX_FILE_SIZE {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    // Shifts every file up by one, dropping the oldest
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        match fs::remove_file(Self::path(&self.dir, MAX_FILES - 1)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        for index in (0..MAX_FILES - 1).rev() {
+            match fs::rename(Self::path(&self.dir, index), Self::path(&self.dir, index + 1)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        *self = LogFile::open(&self.dir)?;
+        Ok(())
+    }
+}
+
+pub struct Logger {
+    buffer: Mutex<VecDeque<LogEntry>>,
+    file: Mutex<Option<LogFile>>,
+    next_id: AtomicU64,
+    // Set once streaming starts; entries are handed to a task that emits them
+    stream: OnceLock<UnboundedSender<LogEntry>>,
+    // Labels of the windows that receive `LOG_EVENT`
+    subscribers: Mutex<HashSet<String>>,
+    // Also print each entry to stderr
+    stderr: bool,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+thread_local! {
+    // Set while emitting, so that anything Tauri logs about the emit itself
+    // isn't streamed again
+    static EMITTING: Cell<bool> = const { Cell::new(false) };
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let time = Utc::now();
+        let entry = LogEntry {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            timestamp: time.to_rfc3339_opts(SecondsFormat::Millis, true),
+            level: record.level().to_string(),
+            message: record.args().to_string(),
+            target: Some(record.target().to_string()),
+            time,
+            severity: record.level(),
+        };
+
+        // A logger must never panic or fail the caller, so errors writing the
+        // file are reported on stderr and otherwise ignored
+        let line = serde_json::to_string(&entry).unwrap_or_default();
+        if self.stderr {
+            eprintln!("{} {:5} {}: {}", entry.timestamp, entry.level, record.target(), entry.message);
+        }
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(log_file) = file.as_mut()
+            && let Err(e) = log_file.write_line(&line)
+        {
+            eprintln!("Failed to write log file: {}", e);
+        }
+        drop(file);
+
+        if let Some(stream) = self.stream.get()
+            && !EMITTING.get()
+        {
+            let _ = stream.send(entry.clone());
+        }
+
+        let mut buffer = self.buffer.lock().unwrap_or_else(PoisonError::into_inner);
+        if buffer.len() == BUFFER_CAPACITY {
+            buffer.pop_front();
+        }
+        buffer.push_back(entry);
+    }
+
+    fn flush(&self) {
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(log_file) = file.as_mut() {
+            let _ = log_file.file.flush();
+        }
+    }
+}
+
+fn log_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".ultralist").join("logs"))
+}
+
+// Installs the logger. Without a writable log directory entries are still
+// kept in memory. `ULTRALIST_LOG_STDERR=1` mirrors entries to stderr.
+pub fn init() {
+    let file = log_dir().and_then(|dir| match LogFile::open(&dir) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            eprintln!("Failed to open log file in {}: {}", dir.display(), e);
+            None
+        }
+    });
+    let logger = LOGGER.get_or_init(|| Logger {
+        buffer: Mutex::new(VecDeque::with_capacity(BUFFER_CAPACITY)),
+        file: Mutex::new(file),
+        next_id: AtomicU64::new(1),
+        stream: OnceLock::new(),
+        subscribers: Mutex::new(HashSet::new()),
+        stderr: std::env::var("ULTRALIST_LOG_STDERR").as_deref() == Ok("1"),
+    });
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(DEFAULT_LEVEL);
+    }
+}
+
+// Starts pushing new entries to subscribed windows. Emitting happens on a
+// separate task so that logging never waits on the webview.
+pub fn start_streaming(app: AppHandle) {
+    let Some(logger) = LOGGER.get() else {
+        return;
+    };
+    let (sender, mut receiver) = mpsc::unbounded_channel::<LogEntry>();
+    if logger.stream.set(sender).is_err() {
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        while let Some(entry) = receiver.recv().await {
+            let labels: Vec<String> = logger
+                .subscribers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .iter()
+                .cloned()
+                .collect();
+            EMITTING.set(true);
+            for label in &labels {
+                if let Err(e) = app.emit_to(label.as_str(), LOG_EVENT, &entry) {
+                    eprintln!("Failed to stream log entry to {}: {}", label, e);
+                }
+            }
+            EMITTING.set(false);
+        }
+    });
+}
+
+pub fn subscribe(label: &str) {
+    if let Some(logger) = LOGGER.get() {
+        let mut subscribers = logger.subscribers.lock().unwrap_or_else(PoisonError::into_inner);
+        subscribers.insert(label.to_string());
+    }
+}
+
+pub fn unsubscribe(label: &str) {
+    if let Some(logger) = LOGGER.get() {
+        let mut subscribers = logger.subscribers.lock().unwrap_or_else(PoisonError::into_inner);
+        subscribers.remove(label);
+    }
+}
+
+// Records a line from the frontend under `react` or `react::<target>`
+pub fn log_frontend(level: &str, target: Option<&str>, message: &str) -> Result<(), String> {
+    let level = Level::from_str(level.trim()).map_err(|_| {
+        format!("Invalid log level '{}'; expected error, warn, info, debug or trace", level)
+    })?;
+    let target = match target.map(str::trim).filter(|t| !t.is_empty()) {
+        Some(target)
+            if target == FRONTEND_TARGET
+                || target.strip_prefix(FRONTEND_TARGET).is_some_and(|rest| rest.starts_with("::")) =>
+        {
+            target.to_string()
+        }
+        Some(target) => format!("{}::{}", FRONTEND_TARGET, target),
+        None => FRONTEND_TARGET.to_string(),
+    };
+    log::log!(target: &target, level, "{}", message);
+    Ok(())
+}
+
+pub fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    LevelFilter::from_str(level.trim()).map_err(|_| {
+        format!("Invalid log level '{}'; expected off, error, warn, info, debug or trace", level)
+    })
+}
+
+pub fn level() -> LevelFilter {
+    log::max_level()
+}
+
+pub fn set_level(level: LevelFilter) {
+    log::set_max_level(level);
+}
+
+// Applies the level saved in settings, if any
+pub async fn load_level(db: &DatabasePool) {
+    let saved = db
+        .read(|db| db.get_setting(LEVEL_SETTING).map_err(AppError::from))
+        .await;
+    match saved {
+        Ok(Some(level)) => match parse_level(&level) {
+            Ok(level) => set_level(level),
+            Err(e) => log::warn!("Ignoring saved log level: {}", e),
+        },
+        Ok(None) => {}
+        Err(e) => log::warn!("Failed to load log level: {}", e),
+    }
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| format!("Invalid time '{}'; expected an RFC 3339 timestamp", value))
+}
+
+// Buffered entries matching the filter, oldest first
+pub fn entries(filter: &LogFilter) -> Result<Vec<LogEntry>, String> {
+    let level = filter
+        .level
+        .as_deref()
+        .map(parse_level)
+        .transpose()?
+        .unwrap_or(LevelFilter::Trace);
+    let since = filter.since.as_deref().map(parse_time).transpose()?;
+    let until = filter.until.as_deref().map(parse_time).transpose()?;
+    let target = filter.target.as_deref().map(str::to_lowercase);
+
+    let Some(logger) = LOGGER.get() else {
+        return Ok(Vec::new());
+    };
+    let buffer = logger.buffer.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut matching: Vec<LogEntry> = buffer
+        .iter()
+        .filter(|entry| entry.severity <= level)
+        .filter(|entry| filter.after_id.is_none_or(|id| entry.id > id))
+        .filter(|entry| since.is_none_or(|since| entry.time >= since))
+        .filter(|entry| until.is_none_or(|until| entry.time <= until))
+        .filter(|entry| {
+            target.as_ref().is_none_or(|target| {
+                entry.target.as_ref().is_some_and(|t| t.to_lowercase().contains(target))
+            })
+        })
+        .cloned()
+        .collect();
+
+    if let Some(limit) = filter.limit
+        && matching.len() > limit
+    {
+        matching.drain(..matching.len() - limit);
+    }
+    Ok(matching)
+}
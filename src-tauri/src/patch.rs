@@ -0,0 +1,121 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// A field in an update request. A missing field leaves the value as it is,
+// `null` clears it and anything else replaces it. Request fields need
+// `#[serde(default)]` so that a missing field becomes `Unchanged`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Patch<T> {
+    #[default]
+    Unchanged,
+    Clear,
+    Set(T),
+}
+
+impl<T> Patch<T> {
+    pub fn is_unchanged(&self) -> bool {
+        matches!(self, Patch::Unchanged)
+    }
+
+    pub fn try_map<U, E>(self, f: impl FnOnce(T) -> Result<U, E>) -> Result<Patch<U>, E> {
+        Ok(match self {
+            Patch::Unchanged => Patch::Unchanged,
+            Patch::Clear => Patch::Clear,
+            Patch::Set(value) => Patch::Set(f(value)?),
+        })
+    }
+
+    // Writes the change into an optional field, returning whether it changed
+    // anything
+    pub fn apply(self, target: &mut Option<T>) -> bool
+    where
+        T: PartialEq,
+    {
+        let value = match self {
+            Patch::Unchanged => return false,
+            Patch::Clear => None,
+            Patch::Set(value) => Some(value),
+        };
+        let changed = *target != value;
+        *target = value;
+        changed
+    }
+}
+
+impl<T> Patch<Option<T>> {
+    // For values that parse to "nothing", such as a blank due date
+    pub fn flatten(self) -> Patch<T> {
+        match self {
+            Patch::Unchanged => Patch::Unchanged,
+            Patch::Clear | Patch::Set(None) => Patch::Clear,
+            Patch::Set(Some(value)) => Patch::Set(value),
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            Some(value) => Patch::Set(value),
+            None => Patch::Clear,
+        })
+    }
+}
+
+// Pair with `skip_serializing_if = "Patch::is_unchanged"`, otherwise an
+// unchanged field is written as `null`
+impl<T: Serialize> Serialize for Patch<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Patch::Set(value) => serializer.serialize_some(value),
+            Patch::Unchanged | Patch::Clear => serializer.serialize_none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Request {
+        #[serde(default, skip_serializing_if = "Patch::is_unchanged")]
+        value: Patch<i32>,
+    }
+
+    fn parse(json: &str) -> Patch<i32> {
+        serde_json::from_str::<Request>(json).unwrap().value
+    }
+
+    #[test]
+    fn absent_is_unchanged_null_clears_and_a_value_sets() {
+        assert_eq!(parse("{}"), Patch::Unchanged);
+        assert_eq!(parse(r#"{"value":null}"#), Patch::Clear);
+        assert_eq!(parse(r#"{"value":3}"#), Patch::Set(3));
+    }
+
+    #[test]
+    fn serializes_back_to_the_same_json() {
+        for json in ["{}", r#"{"value":null}"#, r#"{"value":3}"#] {
+            assert_eq!(serde_json::to_string(&Request { value: parse(json) }).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn apply_reports_whether_the_value_changed() {
+        let mut target = Some(1);
+        assert!(!Patch::Unchanged.apply(&mut target));
+        assert_eq!(target, Some(1));
+        assert!(!Patch::Set(1).apply(&mut target));
+        assert!(Patch::Clear.apply(&mut target));
+        assert_eq!(target, None);
+        assert!(Patch::Set(2).apply(&mut target));
+        assert_eq!(target, Some(2));
+    }
+
+    #[test]
+    fn flatten_treats_a_parsed_nothing_as_clear() {
+        assert_eq!(Patch::Set(None::<i32>).flatten(), Patch::Clear);
+        assert_eq!(Patch::Set(Some(4)).flatten(), Patch::Set(4));
+        assert_eq!(Patch::<Option<i32>>::Unchanged.flatten(), Patch::Unchanged);
+    }
+}
@@ -0,0 +1,165 @@
+use crate::commands::DatabaseState;
+use crate::database::{DueReminder, PoolError};
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Notify;
+
+// The scheduler never sleeps longer than this, so reminders whose times were
+// changed without waking it (e.g. a due date edit) are still picked up soon.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+const MIN_SLEEP: Duration = Duration::from_secs(1);
+
+// Wait before retrying after the database couldn't be reached, doubling with
+// each failure in a row up to `MAX_SLEEP`
+fn retry_delay(failures: u32) -> Duration {
+    MIN_SLEEP.saturating_mul(2u32.saturating_pow(failures)).min(MAX_SLEEP)
+}
+
+// Delivers a reminder to the user
+pub trait Notifier: Send + Sync + 'static {
+    fn notify(&self, reminder: &DueReminder);
+}
+
+fn notification_text(reminder: &DueReminder) -> (String, String) {
+    let title = if reminder.missed {
+        format!("Missed reminder: {}", reminder.task_title)
+    } else {
+        format!("Reminder: {}", reminder.task_title)
+    };
+    let body = match &reminder.due_date {
+        Some(due) => format!("Due {}", due),
+        None => "No due date".to_string(),
+    };
+    (title, body)
+}
+
+// Native desktop notifications through tauri-plugin-notification
+pub struct NativeNotifier {
+    app: AppHandle,
+}
+
+impl NativeNotifier {
+    pub fn new(app: AppHandle) -> Self {
+        NativeNotifier { app }
+    }
+}
+
+impl Notifier for NativeNotifier {
+    fn notify(&self, reminder: &DueReminder) {
+        use tauri_plugin_notification::NotificationExt;
+
+        let (title, body) = notification_text(reminder);
+        if let Err(e) = self.app.notification().builder().title(title).body(body).show() {
+            log::warn!("Failed to show notification for reminder {}: {}", reminder.reminder_id, e);
+        }
+    }
+}
+
+// Logs reminders instead of showing them; for tests and headless runs
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    fn notify(&self, reminder: &DueReminder) {
+        let (title, body) = notification_text(reminder);
+        log::info!("Reminder: {} ({})", title, body);
+    }
+}
+
+// `ULTRALIST_NOTIFIER=log` swaps native notifications for log entries
+pub fn notifier_from_env(app: AppHandle) -> Arc<dyn Notifier> {
+    match std::env::var("ULTRALIST_NOTIFIER").as_deref() {
+        Ok("log") => Arc::new(LogNotifier),
+        _ => Arc::new(NativeNotifier::new(app)),
+    }
+}
+
+// Handle to the background reminder loop. Commands that add or move
+// reminders call `wake` so the loop recomputes its next deadline.
+#[derive(Clone, Default)]
+pub struct ReminderScheduler {
+    wake: Arc<Notify>,
+}
+
+impl ReminderScheduler {
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    // Spawns the loop on the Tauri (tokio) runtime. Its first pass delivers
+    // anything that came due while the app was closed.
+    pub fn start(&self, app: AppHandle, notifier: Arc<dyn Notifier>) {
+        let wake = self.wake.clone();
+        tauri::async_runtime::spawn(async move {
+            run(app, notifier, wake).await;
+        });
+    }
+}
+
+async fn run(app: AppHandle, notifier: Arc<dyn Notifier>, wake: Arc<Notify>) {
+    let mut failures = 0;
+    loop {
+        let db = app.state::<DatabaseState>();
+        let pending = db
+            .write(|db| -> Result<_, PoolError> {
+                let due = db.take_due_reminders(Utc::now()).unwrap_or_else(|e| {
+                    log::error!("Failed to load due reminders: {}", e);
+                    Vec::new()
+                });
+                let next = db.next_reminder_time().unwrap_or_else(|e| {
+                    log::error!("Failed to load next reminder time: {}", e);
+                    None
+                });
+                Ok((due, next))
+            })
+            .await;
+        let (due, next) = match pending {
+            Ok(pending) => {
+                failures = 0;
+                pending
+            }
+            Err(e) => {
+                let delay = retry_delay(failures);
+                failures += 1;
+                log::error!("Reminder scheduler couldn't reach the database, retrying in {:?}: {}", delay, e);
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+        };
+
+        for reminder in &due {
+            log::debug!(
+                "Delivering reminder {} for task {}{}",
+                reminder.reminder_id,
+                reminder.task_id,
+                if reminder.missed { " (missed)" } else { "" }
+            );
+            notifier.notify(reminder);
+        }
+
+        let sleep_for = next
+            .map(|at| (at - Utc::now()).to_std().unwrap_or(MIN_SLEEP))
+            .unwrap_or(MAX_SLEEP)
+            .clamp(MIN_SLEEP, MAX_SLEEP);
+
+        tokio::select! {
+            _ = tokio::time::sleep(sleep_for) => {}
+            _ = wake.notified() => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_back_off_up_to_the_longest_sleep() {
+        assert_eq!(retry_delay(0), MIN_SLEEP);
+        assert_eq!(retry_delay(1), MIN_SLEEP * 2);
+        assert_eq!(retry_delay(3), MIN_SLEEP * 8);
+        assert_eq!(retry_delay(10), MAX_SLEEP);
+        assert_eq!(retry_delay(u32::MAX), MAX_SLEEP);
+    }
+}
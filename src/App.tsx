@@ -40,11 +40,13 @@ const App = (): JSX.Element => {
     updateTask,
     deleteTask,
     toggleTaskCompletion,
-    toggleSubtaskCompletion,
+    toggleSubtask,
     createProject,
     deleteProject,
     createFolder,
     deleteFolder,
+    undo,
+    redo,
     updateTheme,
   } = useDatabase();
 
@@ -296,68 +298,16 @@ const App = (): JSX.Element => {
     }
   };
 
+  // The subtask is looked up by id on the backend, so the task id isn't needed
   const handleToggleSubtask = async (
-    taskId: string,
+    _taskId: string,
     subtaskId: string
   ): Promise<void> => {
-    console.log("=== SUBTASK TOGGLE DEBUG ===");
-    console.log("handleToggleSubtask called with:", { taskId, subtaskId });
-    console.log(
-      "All tasks:",
-      tasks.map((t) => ({
-        id: t.id,
-        title: t.title,
-        subtaskCount: t.subtasks.length,
-      }))
-    );
-
-    const task = tasks.find((t) => t.id === taskId);
-    console.log("Found task:", task ? task.title : "null");
-
-    if (task) {
-      console.log(
-        "Task subtasks:",
-        task.subtasks.map((st) => ({
-          id: st.id,
-          text: st.text,
-          completed: st.completed,
-        }))
-      );
-      const subtask = task.subtasks.find((st) => st.id === subtaskId);
-      console.log("Found subtask:", subtask);
-
-      if (subtask) {
-        console.log("Subtask details:", {
-          id: subtask.id,
-          text: subtask.text,
-          currentCompletion: subtask.completed,
-        });
-        console.log("Will call toggleSubtaskCompletion with:", {
-          subtaskId: subtask.id,
-          newCompletion: !subtask.completed,
-        });
-        try {
-          await toggleSubtaskCompletion(subtask.id, !subtask.completed);
-          console.log("Successfully toggled subtask completion");
-        } catch (error) {
-          console.error("Failed to toggle subtask:", error);
-          console.error("Error details:", JSON.stringify(error, null, 2));
-        }
-      } else {
-        console.error("Subtask not found in task:", subtaskId);
-        console.error(
-          "Available subtask IDs:",
-          task.subtasks.map((st) => st.id)
-        );
-      }
-    } else {
-      console.error("Task not found:", taskId);
-      console.error(
-        "Available task IDs:",
-        tasks.map((t) => t.id)
-      );
+    try {
+      await toggleSubtask(subtaskId);
+    } catch (error) {
+      console.error("Failed to toggle subtask:", error);
     }
-    console.log("=== END SUBTASK TOGGLE DEBUG ===");
   };
 
   const handleDeleteTask = (id: string): void => {
@@ -503,6 +453,12 @@ const App = (): JSX.Element => {
       console.log("Toggle completed tasks not yet implemented");
     },
     onToggleTheme: () => toggleDarkMode(),
+    onUndo: () => {
+      undo().catch((error) => console.error("Failed to undo:", error));
+    },
+    onRedo: () => {
+      redo().catch((error) => console.error("Failed to redo:", error));
+    },
   });
 
   // Show loading state while database is initializing
@@ -829,7 +785,7 @@ const App = (): JSX.Element => {
       <ConfirmationModal
         isOpen={showDeleteConfirmation}
         title="Delete Task"
-        message={`Are you sure you want to delete "${taskToDelete?.title}"? This action cannot be undone.`}
+        message={`Are you sure you want to delete "${taskToDelete?.title}"? It will be moved to the trash.`}
         confirmText="Delete"
         cancelText="Cancel"
         onConfirm={confirmDeleteTask}
@@ -39,6 +39,8 @@ const KeyboardShortcutsModal: React.FC<KeyboardShortcutsModalProps> = ({
         { key: "Alt + E", action: "Edit most recent task" },
         { key: "Alt + D", action: "Delete most recent task" },
         { key: "Alt + C", action: "Switch to NLP (Natural Language) mode" },
+        { key: "Ctrl + Z", action: "Undo last change" },
+        { key: "Ctrl + Shift + Z", action: "Redo" },
       ],
     },
     {
@@ -1,5 +1,10 @@
 import React, { useState, useEffect, useRef } from "react";
 import { Task, Project } from "../types";
+import {
+  pomodoroService,
+  settingsService,
+  PomodoroSettings as StoredPomodoroSettings,
+} from "../services/databaseService";
 import {
   Play,
   Pause,
@@ -51,6 +56,35 @@ interface PomodoroSettings {
   sessionsUntilLongBreak: number;
 }
 
+// Setting key remembering the task picked for focus
+const SELECTED_TASK_SETTING = "pomodoro_selected_task";
+
+const fromStoredSettings = (
+  stored: StoredPomodoroSettings
+): PomodoroSettings => ({
+  workDuration: stored.work_duration,
+  shortBreakDuration: stored.short_break_duration,
+  longBreakDuration: stored.long_break_duration,
+  sessionsUntilLongBreak: stored.sessions_until_long_break,
+});
+
+const toStoredSettings = (
+  settings: PomodoroSettings
+): StoredPomodoroSettings => ({
+  work_duration: settings.workDuration,
+  short_break_duration: settings.shortBreakDuration,
+  long_break_duration: settings.longBreakDuration,
+  sessions_until_long_break: settings.sessionsUntilLongBreak,
+});
+
+const today = () => {
+  const now = new Date();
+  const pad = (n: number) => n.toString().padStart(2, "0");
+  return `${now.getFullYear()}-${pad(now.getMonth() + 1)}-${pad(
+    now.getDate()
+  )}`;
+};
+
 const PomodoroView: React.FC<PomodoroViewProps> = ({
   tasks,
   projects,
@@ -65,7 +99,7 @@ const PomodoroView: React.FC<PomodoroViewProps> = ({
   const [timeLeft, setTimeLeft] = useState(25 * 60); // 25 minutes in seconds
   const [currentPhase, setCurrentPhase] = useState<PomodoroPhase>("work");
   const [completedSessions, setCompletedSessions] = useState(0);
-  const [selectedTask, setSelectedTask] = useState<Task | null>(null);
+  const [selectedTaskId, setSelectedTaskId] = useState<string | null>(null);
   const [showSettings, setShowSettings] = useState(false);
   const [selectedTimerType, setSelectedTimerType] =
     useState<TimerType>("pomodoro");
@@ -81,6 +115,86 @@ const PomodoroView: React.FC<PomodoroViewProps> = ({
   });
 
   const intervalRef = useRef<number | null>(null);
+  // The stored session being timed and its planned length in seconds
+  const sessionRef = useRef<{ id: number; plannedSeconds: number } | null>(
+    null
+  );
+
+  const selectedTask = tasks.find((task) => task.id === selectedTaskId) || null;
+
+  // Restore settings, the selected task and today's session count. A
+  // session left running when the app closed can't be resumed, so it is
+  // recorded as interrupted.
+  useEffect(() => {
+    const load = async () => {
+      try {
+        const active = await pomodoroService.getActiveSession();
+        if (active) {
+          await pomodoroService.abortSession(active.id);
+        }
+        const stored = fromStoredSettings(
+          await pomodoroService.getSettings()
+        );
+        setSettings(stored);
+        setTimeLeft(stored.workDuration * 60);
+        setSelectedTaskId(
+          await settingsService.getSetting(SELECTED_TASK_SETTING)
+        );
+        const stats = await pomodoroService.getFocusStats({
+          from: today(),
+          to: today(),
+        });
+        setCompletedSessions(stats.completed_sessions);
+      } catch (error) {
+        console.error("Failed to load pomodoro state:", error);
+      }
+    };
+    load();
+  }, []);
+
+  const selectTask = (task: Task) => {
+    setSelectedTaskId(task.id);
+    settingsService
+      .saveSetting(SELECTED_TASK_SETTING, task.id)
+      .catch((error) =>
+        console.error("Failed to save selected pomodoro task:", error)
+      );
+  };
+
+  const updateSettings = (newSettings: PomodoroSettings) => {
+    setSettings(newSettings);
+    pomodoroService
+      .saveSettings(toStoredSettings(newSettings))
+      .catch((error) =>
+        console.error("Failed to save pomodoro settings:", error)
+      );
+  };
+
+  const startSession = () => {
+    const phase = selectedTimerType === "pomodoro" ? currentPhase : "work";
+    pomodoroService
+      .startSession(phase, timeLeft, selectedTaskId)
+      .then((session) => {
+        sessionRef.current = { id: session.id, plannedSeconds: timeLeft };
+      })
+      .catch((error) =>
+        console.error("Failed to start pomodoro session:", error)
+      );
+  };
+
+  // Ends the stored session, if any, with the time actually spent on it
+  const endSession = (interrupted: boolean, secondsLeft: number) => {
+    const session = sessionRef.current;
+    if (!session) return;
+    sessionRef.current = null;
+    const actualSeconds = Math.max(0, session.plannedSeconds - secondsLeft);
+    const end = interrupted
+      ? pomodoroService.abortSession
+      : pomodoroService.finishSession;
+    end(session.id, actualSeconds).catch((error) =>
+      console.error("Failed to end pomodoro session:", error)
+    );
+  };
 
   const getProject = (projectId?: number) =>
     projects.find((p) => p.id === projectId);
@@ -172,6 +286,7 @@ const PomodoroView: React.FC<PomodoroViewProps> = ({
 
   const handlePhaseComplete = () => {
     setIsRunning(false);
+    endSession(false, 0);
 
     if (selectedTimerType === "pomodoro") {
       if (currentPhase === "work") {
@@ -219,11 +334,15 @@ const PomodoroView: React.FC<PomodoroViewProps> = ({
   };
 
   const toggleTimer = () => {
+    if (!isRunning && !sessionRef.current) {
+      startSession();
+    }
     setIsRunning(!isRunning);
   };
 
   const resetTimer = () => {
     setIsRunning(false);
+    endSession(true, timeLeft);
     const currentTimer = getCurrentTimerType();
 
     if (selectedTimerType === "pomodoro") {
@@ -238,6 +357,7 @@ const PomodoroView: React.FC<PomodoroViewProps> = ({
     setSelectedTimerType(timerType);
     setShowTimerDropdown(false);
     setIsRunning(false);
+    endSession(true, timeLeft);
 
     const timer = timerTypes.find((t) => t.id === timerType);
     if (timer) {
@@ -314,7 +434,7 @@ const PomodoroView: React.FC<PomodoroViewProps> = ({
             ? "bg-gray-800 border-gray-600 hover:bg-gray-750"
             : "bg-white border-gray-200 hover:bg-gray-50"
         }`}
-        onClick={() => setSelectedTask(task)}
+        onClick={() => selectTask(task)}
       >
         <div className="flex items-start justify-between mb-2">
           <h4
@@ -726,7 +846,7 @@ const PomodoroView: React.FC<PomodoroViewProps> = ({
                       max="60"
                       value={settings.workDuration}
                       onChange={(e) =>
-                        setSettings({
+                        updateSettings({
                           ...settings,
                           workDuration: parseInt(e.target.value) || 25,
                         })
@@ -752,7 +872,7 @@ const PomodoroView: React.FC<PomodoroViewProps> = ({
                       max="30"
                       value={settings.shortBreakDuration}
                       onChange={(e) =>
-                        setSettings({
+                        updateSettings({
                           ...settings,
                           shortBreakDuration: parseInt(e.target.value) || 5,
                         })
@@ -778,7 +898,7 @@ const PomodoroView: React.FC<PomodoroViewProps> = ({
                       max="60"
                       value={settings.longBreakDuration}
                       onChange={(e) =>
-                        setSettings({
+                        updateSettings({
                           ...settings,
                           longBreakDuration: parseInt(e.target.value) || 15,
                         })
@@ -804,7 +924,7 @@ const PomodoroView: React.FC<PomodoroViewProps> = ({
                       max="10"
                       value={settings.sessionsUntilLongBreak}
                       onChange={(e) =>
-                        setSettings({
+                        updateSettings({
                           ...settings,
                           sessionsUntilLongBreak: parseInt(e.target.value) || 4,
                         })
@@ -6,12 +6,18 @@ import {
   folderService,
   tagService,
   settingsService,
+  historyService,
   convertToFrontendTask,
   convertToBackendTask,
   isInTauriContext,
+  isAppError,
+  describeError,
   type TaskWithDetails,
   type DatabaseProject,
   type DatabaseFolder,
+  type ProjectDeletePolicy,
+  type FolderDeletePolicy,
+  type Operation,
 } from "../services/databaseService";
 import { waitForTauriInitialization } from "../utils/tauriDebug";
 import type { Task, Project, Folder } from "../types";
@@ -78,6 +84,10 @@ export const useDatabase = () => {
           await attemptDataLoad();
           return; // Success!
         } catch (error) {
+          // The backend answered, so Tauri is ready and waiting won't help
+          if (isAppError(error)) {
+            throw error;
+          }
           console.log(
             "Optimistic load failed, will wait for full initialization:",
             error
@@ -97,7 +107,7 @@ export const useDatabase = () => {
       await attemptDataLoad();
     } catch (err) {
       console.error("Database error:", err);
-      setError("Error accessing data - database connection failed");
+      setError(describeError(err, "Error accessing data - database connection failed"));
       setTasks([]);
       setProjects([]);
       setFolders([]);
@@ -222,7 +232,7 @@ export const useDatabase = () => {
         return taskId;
       } catch (err) {
         console.error("Error in useDatabase.createTask:", err);
-        setError("Error accessing data - failed to create task");
+        setError(describeError(err, "Error accessing data - failed to create task"));
         throw err;
       }
     },
@@ -288,7 +298,7 @@ export const useDatabase = () => {
         });
       } catch (err) {
         console.error("Final updateTask error:", err);
-        setError("Error accessing data - failed to update task");
+        setError(describeError(err, "Error accessing data - failed to update task"));
         throw err;
       }
     },
@@ -313,7 +323,7 @@ export const useDatabase = () => {
         await taskService.deleteTask(taskId);
         await loadAllData(); // Reload all data to remove the deleted task
       } catch (err) {
-        setError("Error accessing data - failed to delete task");
+        setError(describeError(err, "Error accessing data - failed to delete task"));
         throw err;
       }
     },
@@ -340,15 +350,15 @@ export const useDatabase = () => {
           await loadAllData(); // Reload all data to get the updated task
         });
       } catch (err) {
-        setError("Error accessing data - failed to toggle task completion");
+        setError(describeError(err, "Error accessing data - failed to toggle task completion"));
         throw err;
       }
     },
     [withScrollPreservation, loadAllData]
   );
 
-  const toggleSubtaskCompletion = useCallback(
-    async (subtaskId: string, completed: boolean) => {
+  const toggleSubtask = useCallback(
+    async (subtaskId: string) => {
       try {
         setError(null);
 
@@ -363,11 +373,11 @@ export const useDatabase = () => {
         }
 
         await withScrollPreservation(async () => {
-          await taskService.toggleSubtaskCompletion(subtaskId, completed);
+          await taskService.toggleSubtask(subtaskId);
           await loadAllData(); // Reload all data to get the updated subtask
         });
       } catch (err) {
-        setError("Error accessing data - failed to toggle subtask completion");
+        setError(describeError(err, "Error accessing data - failed to toggle subtask"));
         throw err;
       }
     },
@@ -406,7 +416,7 @@ export const useDatabase = () => {
         );
         await loadAllData();
       } catch (err) {
-        setError("Error accessing data - failed to create project");
+        setError(describeError(err, "Error accessing data - failed to create project"));
         throw err;
       }
     },
@@ -414,7 +424,7 @@ export const useDatabase = () => {
   );
 
   const deleteProject = useCallback(
-    async (projectId: number) => {
+    async (projectId: number, policy?: ProjectDeletePolicy) => {
       try {
         setError(null);
 
@@ -431,10 +441,10 @@ export const useDatabase = () => {
           );
         }
 
-        await projectService.deleteProject(projectId);
+        await projectService.deleteProject(projectId, policy);
         await loadAllData();
       } catch (err) {
-        setError("Error accessing data - failed to delete project");
+        setError(describeError(err, "Error accessing data - failed to delete project"));
         throw err;
       }
     },
@@ -463,7 +473,7 @@ export const useDatabase = () => {
         await folderService.createFolder(name, color, description || null);
         await loadAllData();
       } catch (err) {
-        setError("Error accessing data - failed to create folder");
+        setError(describeError(err, "Error accessing data - failed to create folder"));
         throw err;
       }
     },
@@ -471,7 +481,7 @@ export const useDatabase = () => {
   );
 
   const deleteFolder = useCallback(
-    async (folderId: number) => {
+    async (folderId: number, policy?: FolderDeletePolicy) => {
       try {
         setError(null);
 
@@ -488,16 +498,49 @@ export const useDatabase = () => {
           );
         }
 
-        await folderService.deleteFolder(folderId);
+        await folderService.deleteFolder(folderId, policy);
         await loadAllData();
       } catch (err) {
-        setError("Error accessing data - failed to delete folder");
+        setError(describeError(err, "Error accessing data - failed to delete folder"));
+        throw err;
+      }
+    },
+    [loadAllData]
+  );
+
+  // Undo / redo. A conflict only means that step can't be taken any more,
+  // so it is logged rather than shown as a database error.
+  const stepHistory = useCallback(
+    async (step: () => Promise<Operation | null>, action: string) => {
+      try {
+        const operation = await step();
+        if (operation) {
+          await loadAllData();
+        }
+        return operation;
+      } catch (err) {
+        if (isAppError(err) && err.kind === "conflict") {
+          console.warn(err.message);
+          await loadAllData();
+          return null;
+        }
+        setError(describeError(err, `Error accessing data - failed to ${action}`));
         throw err;
       }
     },
     [loadAllData]
   );
 
+  const undo = useCallback(
+    () => stepHistory(historyService.undo, "undo"),
+    [stepHistory]
+  );
+
+  const redo = useCallback(
+    () => stepHistory(historyService.redo, "redo"),
+    [stepHistory]
+  );
+
   // Theme operations
   const updateTheme = useCallback(async (newTheme: "light" | "dark") => {
     try {
@@ -516,7 +559,7 @@ export const useDatabase = () => {
       await settingsService.setTheme(newTheme);
       setTheme(newTheme);
     } catch (err) {
-      setError("Error accessing data - failed to update theme");
+      setError(describeError(err, "Error accessing data - failed to update theme"));
       throw err;
     }
   }, []);
@@ -527,7 +570,7 @@ export const useDatabase = () => {
       setError(null);
       await settingsService.saveSetting(key, value);
     } catch (err) {
-      setError(err instanceof Error ? err.message : "Failed to save setting");
+      setError(describeError(err, "Failed to save setting"));
       throw err;
     }
   }, []);
@@ -537,7 +580,7 @@ export const useDatabase = () => {
       setError(null);
       return await settingsService.getSetting(key);
     } catch (err) {
-      setError(err instanceof Error ? err.message : "Failed to get setting");
+      setError(describeError(err, "Failed to get setting"));
       throw err;
     }
   }, []);
@@ -557,7 +600,7 @@ export const useDatabase = () => {
     updateTask,
     deleteTask,
     toggleTaskCompletion,
-    toggleSubtaskCompletion,
+    toggleSubtask,
 
     // Project operations
     createProject,
@@ -567,6 +610,10 @@ export const useDatabase = () => {
     createFolder,
     deleteFolder,
 
+    // History operations
+    undo,
+    redo,
+
     // Theme operations
     updateTheme,
 
@@ -16,22 +16,41 @@ interface KeyboardShortcutsConfig {
   onOpenTaskSidebar: () => void;
   onToggleCompletedTasks: () => void;
   onToggleTheme: () => void;
+  onUndo: () => void;
+  onRedo: () => void;
 }
 
 export const useKeyboardShortcuts = (config: KeyboardShortcutsConfig) => {
   const handleKeyDown = useCallback(
     (event: KeyboardEvent) => {
-      // Only trigger if Alt key is pressed
-      if (!event.altKey) {
-        return;
-      }
-
       const key = event.key.toLowerCase();
       const isInputFocused =
         document.activeElement?.tagName === "INPUT" ||
         document.activeElement?.tagName === "TEXTAREA" ||
         (document.activeElement as HTMLElement)?.contentEditable === "true";
 
+      // Ctrl+Z / Ctrl+Shift+Z / Ctrl+Y (Cmd on macOS); inputs keep their own
+      // text undo
+      if ((event.ctrlKey || event.metaKey) && !event.altKey && !isInputFocused) {
+        if (key === "z") {
+          event.preventDefault();
+          if (event.shiftKey) {
+            config.onRedo();
+          } else {
+            config.onUndo();
+          }
+        } else if (key === "y") {
+          event.preventDefault();
+          config.onRedo();
+        }
+        return;
+      }
+
+      // Only trigger if Alt key is pressed
+      if (!event.altKey) {
+        return;
+      }
+
       // Allow Alt + C to work even when input is focused (for switching to NLP mode)
       if (isInputFocused && key !== "c") {
         return;
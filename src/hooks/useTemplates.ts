@@ -1,19 +1,42 @@
-import { useState } from "react";
+import { useEffect, useState } from "react";
 import type { TaskTemplate } from "../types";
+import {
+  convertToFrontendTemplate,
+  templateService,
+} from "../services/databaseService";
 
 export const useTemplates = (initialTemplates: TaskTemplate[] = []) => {
   const [templates, setTemplates] = useState<TaskTemplate[]>(initialTemplates);
 
+  // Stored templates replace the built-in ones once the backend responds
+  useEffect(() => {
+    templateService
+      .listTemplates()
+      .then((stored) => {
+        if (stored.length > 0) {
+          setTemplates(stored.map(convertToFrontendTemplate));
+        }
+      })
+      .catch((error) => console.error("Failed to load templates:", error));
+  }, []);
+
   const addTemplate = (templateData: Omit<TaskTemplate, "id">): void => {
-    const template: TaskTemplate = {
-      id: Date.now(),
-      ...templateData,
-    };
-    setTemplates([...templates, template]);
+    templateService
+      .createTemplate(templateData)
+      .then((created) =>
+        setTemplates((current) => [
+          ...current,
+          convertToFrontendTemplate(created),
+        ])
+      )
+      .catch((error) => console.error("Failed to save template:", error));
   };
 
   const deleteTemplate = (id: number): void => {
-    setTemplates(templates.filter((t) => t.id !== id));
+    setTemplates((current) => current.filter((t) => t.id !== id));
+    templateService
+      .deleteTemplate(id)
+      .catch((error) => console.error("Failed to delete template:", error));
   };
 
   const getTemplateById = (id: number): TaskTemplate | undefined => {
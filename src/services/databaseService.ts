@@ -1,4 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
+import type { FilterBy, SavedSearch, TaskStatus, TaskTemplate } from "../types";
 import {
   debugTauriContext,
   logTauriDiagnostic,
@@ -22,6 +23,55 @@ export const isInTauriContext = (): boolean => {
   }
 };
 
+// Error returned by commands (see src-tauri/src/error.rs)
+export type AppError =
+  | { kind: "not_found"; message: string; entity: string | null; id: string | null }
+  | {
+      kind: "validation";
+      message: string;
+      field: string | null;
+      // Character offsets of the part at fault, e.g. a query term
+      span: { start: number; end: number } | null;
+    }
+  | { kind: "conflict"; message: string; details: ConflictDetails | null }
+  | { kind: "busy"; message: string }
+  | { kind: "storage"; message: string }
+  | { kind: "internal"; message: string };
+
+export type ConflictDetails = {
+  reason: "wip_limit_reached";
+  column_id: number;
+  column_name: string;
+  wip_limit: number;
+};
+
+// Any structured error from a command
+const isCommandError = (error: unknown): error is { kind: string } =>
+  typeof error === "object" && error !== null && "kind" in error;
+
+export const isAppError = (error: unknown): error is AppError =>
+  isCommandError(error) && "message" in error;
+
+// Only a busy database is worth retrying as is; anything else fails again
+export const isTransientError = (error: unknown): boolean =>
+  isAppError(error) && error.kind === "busy";
+
+// The backend's message when it tells the user something they can act on,
+// otherwise `fallback`
+export const describeError = (error: unknown, fallback: string): string => {
+  if (
+    isAppError(error) &&
+    (error.kind === "not_found" ||
+      error.kind === "validation" ||
+      error.kind === "conflict")
+  ) {
+    return error.message;
+  }
+  return fallback;
+};
+
+const BUSY_RETRY_DELAY_MS = 250;
+
 // Enhanced invoke wrapper with Tauri initialization waiting
 export const safeInvoke = async <T = any>(
   command: string,
@@ -36,6 +86,15 @@ export const safeInvoke = async <T = any>(
         console.log(`[DB] Command '${command}' completed successfully`);
         return result;
       } catch (invokeError) {
+        // The command ran and failed, so waiting for Tauri won't help
+        if (isCommandError(invokeError)) {
+          if (!isTransientError(invokeError)) {
+            throw invokeError;
+          }
+          console.log(`[DB] Database busy, retrying ${command}...`);
+          await new Promise((resolve) => setTimeout(resolve, BUSY_RETRY_DELAY_MS));
+          return await invoke<T>(command, args);
+        }
         // If the command fails, it might be because Tauri isn't fully ready
         console.log(
           `[DB] Direct invoke failed, checking Tauri initialization...`
@@ -68,16 +127,68 @@ export const safeInvoke = async <T = any>(
 };
 
 // Types matching the Rust backend
+export type Priority = "low" | "medium" | "high";
+
 export interface DatabaseTask {
   id: string;
   title: string;
   description: string;
+  // `YYYY-MM-DD`, or an RFC 3339 timestamp when a time was set
   due_date: string | null;
-  priority: string;
+  priority: Priority;
   completed: boolean;
+  status: TaskStatus;
+  column_id: number | null;
   project_id: number | null;
+  recurrence: RecurrenceRule | null;
+  series_id: string | null;
   created_at: string;
   updated_at: string;
+  // Set while the task is completed
+  completed_at: string | null;
+}
+
+export interface RecurrenceRule {
+  frequency: "daily" | "weekly" | "monthly" | "yearly";
+  // 1 to 1200
+  interval: number;
+  weekdays: ("Mon" | "Tue" | "Wed" | "Thu" | "Fri" | "Sat" | "Sun")[];
+  // 1 to 31
+  month_day: number | null;
+  // Inclusive YYYY-MM-DD date
+  until: string | null;
+  // At least 1
+  count: number | null;
+}
+
+export interface SeriesCompletion {
+  task_id: string;
+  occurrence: number;
+  due_date: string | null;
+  completed_at: string;
+  on_time: boolean;
+}
+
+export interface SeriesHistory {
+  series_id: string;
+  completions: SeriesCompletion[];
+  current_streak: number;
+  longest_streak: number;
+}
+
+// One field change in a task's audit trail; lists are JSON arrays
+export interface TaskHistoryEntry {
+  id: number;
+  task_id: string;
+  // A column such as "due_date", or "created", "subtask_added",
+  // "subtask_removed", "subtask_text", "subtask_completed", "subtask_order"
+  // or "tags"
+  field: string;
+  old_value: string | null;
+  new_value: string | null;
+  // The action that made the change, e.g. "Edit task" or "Undo: Delete task"
+  source: string;
+  changed_at: string;
 }
 
 export interface DatabaseSubtask {
@@ -85,6 +196,8 @@ export interface DatabaseSubtask {
   task_id: string;
   text: string;
   completed: boolean;
+  // Order within the task, from 0
+  position: number;
 }
 
 export interface DatabaseProject {
@@ -102,12 +215,128 @@ export interface DatabaseFolder {
   description: string | null;
 }
 
+export interface WorkflowColumn {
+  id: number;
+  project_id: number;
+  name: string;
+  position: number;
+  status: TaskStatus;
+  wip_limit: number | null;
+}
+
+export interface WorkflowColumnInput {
+  id: number | null;
+  name: string;
+  status: TaskStatus;
+  wip_limit: number | null;
+}
+
+export type ProjectDeletePolicy =
+  | { mode: "reassign_tasks"; project_id: number }
+  | { mode: "move_tasks_to_inbox" }
+  | { mode: "delete_tasks" };
+
+export type FolderDeletePolicy =
+  | { mode: "reparent_projects"; folder_id: number | null }
+  | { mode: "delete_projects"; task_policy: ProjectDeletePolicy };
+
+export type ReminderStatus = "pending" | "fired" | "dismissed";
+
+// Either `remind_at` (absolute) or `offset_minutes` (before the due date) is set
+export interface Reminder {
+  id: string;
+  task_id: string;
+  remind_at: string | null;
+  // At most five years either side
+  offset_minutes: number | null;
+  fire_at: string | null;
+  status: ReminderStatus;
+  fired_at: string | null;
+}
+
 export interface TaskWithDetails {
   task: DatabaseTask;
   subtasks: DatabaseSubtask[];
   tags: string[];
 }
 
+// Matches are wrapped in <mark> tags; a lower rank is a better match
+export interface TaskSearchResult extends TaskWithDetails {
+  rank: number;
+  title_highlight: string;
+  snippet: string;
+}
+
+export type TaskSortField =
+  | "created_at"
+  | "updated_at"
+  | "due_date"
+  | "priority"
+  | "title";
+
+export interface TaskListRequest {
+  sort?: TaskSortField;
+  direction?: "asc" | "desc";
+  limit?: number;
+  // `next_cursor` of the previous page, with the same sort and direction
+  cursor?: string | null;
+  project_id?: number | null;
+  completed?: boolean | null;
+}
+
+export interface TaskPage {
+  tasks: TaskWithDetails[];
+  next_cursor: string | null;
+}
+
+export interface DatabaseSavedSearch {
+  id: number;
+  name: string;
+  tags: string[];
+  priority: "low" | "medium" | "high" | null;
+  project_id: number | null;
+  filter_by: FilterBy;
+  completed: boolean | null;
+  created_at: string;
+}
+
+export interface SavedSearchCount {
+  id: number;
+  count: number;
+}
+
+export interface DatabaseTemplate {
+  id: number;
+  name: string;
+  description: string | null;
+  title: string;
+  task_description: string;
+  priority: "low" | "medium" | "high";
+  subtasks: string[];
+  tags: string[];
+  project_id: number | null;
+  created_at: string;
+}
+
+export interface ProjectTemplateTask {
+  title: string;
+  description: string;
+  priority: "low" | "medium" | "high";
+  due_offset_days: number | null;
+  due_time: string | null;
+  subtasks: string[];
+  tags: string[];
+}
+
+export interface ProjectTemplate {
+  id: number;
+  name: string;
+  color: string;
+  description: string | null;
+  tasks: ProjectTemplateTask[];
+  created_at: string;
+}
+
 export interface CreateTaskRequest {
   title: string;
   description: string;
@@ -116,18 +345,21 @@ export interface CreateTaskRequest {
   project_id: number | null;
   subtasks: string[];
   tags: string[];
+  recurrence?: RecurrenceRule | null;
 }
 
+// Omitted fields are left unchanged; `null` clears the field
 export interface UpdateTaskRequest {
   id: string;
   title?: string;
-  description?: string;
-  due_date?: string;
+  description?: string | null;
+  due_date?: string | null;
   priority?: string;
-  project_id?: number;
+  project_id?: number | null;
   completed?: boolean;
   subtasks?: string[];
   tags?: string[];
+  recurrence?: RecurrenceRule | null;
 }
 
 // Task operations
@@ -140,6 +372,27 @@ export const taskService = {
     return await safeInvoke<TaskWithDetails[]>("get_all_tasks");
   },
 
+  async getTask(taskId: string): Promise<TaskWithDetails | null> {
+    return await safeInvoke<TaskWithDetails | null>("get_task", { taskId });
+  },
+
+  async listTasks(request: TaskListRequest = {}): Promise<TaskPage> {
+    return await safeInvoke<TaskPage>("list_tasks", { request });
+  },
+
+  // 50 results by default, at most 500
+  async searchTasks(query: string, limit?: number): Promise<TaskSearchResult[]> {
+    return await safeInvoke<TaskSearchResult[]>("search_tasks", {
+      query,
+      limit: limit ?? null,
+    });
+  },
+
+  // e.g. `tag:work priority:high due:<2026-11-01 project:"Health" -completed`
+  async queryTasks(query: string): Promise<TaskWithDetails[]> {
+    return await safeInvoke<TaskWithDetails[]>("query_tasks", { query });
+  },
+
   async updateTask(request: UpdateTaskRequest): Promise<void> {
     try {
       console.log("taskService.updateTask called with:", request);
@@ -165,6 +418,19 @@ export const taskService = {
     });
   },
 
+  async setTaskStatus(taskId: string, status: TaskStatus): Promise<void> {
+    return await safeInvoke<void>("set_task_status", { taskId, status });
+  },
+
+  async getSeriesHistory(seriesId: string): Promise<SeriesHistory> {
+    return await safeInvoke<SeriesHistory>("get_series_history", { seriesId });
+  },
+
+  // Newest change first
+  async getTaskHistory(taskId: string): Promise<TaskHistoryEntry[]> {
+    return await safeInvoke<TaskHistoryEntry[]>("get_task_history", { taskId });
+  },
+
   async toggleSubtaskCompletion(
     subtaskId: string,
     completed: boolean
@@ -174,6 +440,39 @@ export const taskService = {
       completed,
     });
   },
+
+  async addSubtask(taskId: string, text: string): Promise<DatabaseSubtask> {
+    return await safeInvoke<DatabaseSubtask>("add_subtask", { taskId, text });
+  },
+
+  async renameSubtask(
+    subtaskId: string,
+    text: string
+  ): Promise<DatabaseSubtask> {
+    return await safeInvoke<DatabaseSubtask>("rename_subtask", {
+      subtaskId,
+      text,
+    });
+  },
+
+  async deleteSubtask(subtaskId: string): Promise<void> {
+    return await safeInvoke<void>("delete_subtask", { subtaskId });
+  },
+
+  // `subtaskIds` must list every subtask of the task, in the new order
+  async reorderSubtasks(
+    taskId: string,
+    subtaskIds: string[]
+  ): Promise<DatabaseSubtask[]> {
+    return await safeInvoke<DatabaseSubtask[]>("reorder_subtasks", {
+      taskId,
+      subtaskIds,
+    });
+  },
+
+  async toggleSubtask(subtaskId: string): Promise<DatabaseSubtask> {
+    return await safeInvoke<DatabaseSubtask>("toggle_subtask", { subtaskId });
+  },
 };
 
 // Project operations
@@ -196,8 +495,218 @@ export const projectService = {
     return await safeInvoke<DatabaseProject[]>("get_all_projects");
   },
 
-  async deleteProject(projectId: number): Promise<void> {
-    return await safeInvoke<void>("delete_project", { projectId });
+  async deleteProject(
+    projectId: number,
+    policy?: ProjectDeletePolicy
+  ): Promise<void> {
+    return await safeInvoke<void>("delete_project", { projectId, policy });
+  },
+};
+
+// Workflow operations (per-project Kanban columns)
+export const workflowService = {
+  async getProjectWorkflow(projectId: number): Promise<WorkflowColumn[]> {
+    return await safeInvoke<WorkflowColumn[]>("get_project_workflow", {
+      projectId,
+    });
+  },
+
+  async setProjectWorkflow(
+    projectId: number,
+    columns: WorkflowColumnInput[]
+  ): Promise<WorkflowColumn[]> {
+    return await safeInvoke<WorkflowColumn[]>("set_project_workflow", {
+      projectId,
+      columns,
+    });
+  },
+
+  async moveTaskToColumn(taskId: string, columnId: number): Promise<void> {
+    return await safeInvoke<void>("move_task_to_column", { taskId, columnId });
+  },
+};
+
+// Reminder operations
+export const reminderService = {
+  async addReminder(
+    taskId: string,
+    remindAt: string | null,
+    offsetMinutes: number | null
+  ): Promise<Reminder> {
+    return await safeInvoke<Reminder>("add_reminder", {
+      taskId,
+      remindAt,
+      offsetMinutes,
+    });
+  },
+
+  async getRemindersForTask(taskId: string): Promise<Reminder[]> {
+    return await safeInvoke<Reminder[]>("get_reminders_for_task", { taskId });
+  },
+
+  async deleteReminder(reminderId: string): Promise<void> {
+    return await safeInvoke<void>("delete_reminder", { reminderId });
+  },
+
+  // 1 minute to 1 week
+  async snoozeReminder(reminderId: string, minutes: number): Promise<void> {
+    return await safeInvoke<void>("snooze_reminder", { reminderId, minutes });
+  },
+
+  async dismissReminder(reminderId: string): Promise<void> {
+    return await safeInvoke<void>("dismiss_reminder", { reminderId });
+  },
+};
+
+// Saved search operations (smart lists evaluated in the backend)
+const toSavedSearchInput = (search: Omit<SavedSearch, "id">) => ({
+  name: search.name,
+  tags: search.tags,
+  priority: search.priority ?? null,
+  project_id: search.projectId ?? null,
+  filter_by: search.filterBy ?? "all",
+  completed: search.completed ?? null,
+});
+
+export const convertToFrontendSavedSearch = (
+  search: DatabaseSavedSearch
+): SavedSearch => ({
+  id: search.id,
+  name: search.name,
+  tags: search.tags,
+  priority: search.priority ?? undefined,
+  projectId: search.project_id ?? undefined,
+  filterBy: search.filter_by,
+  completed: search.completed ?? undefined,
+});
+
+export const savedSearchService = {
+  async createSavedSearch(
+    search: Omit<SavedSearch, "id">
+  ): Promise<DatabaseSavedSearch> {
+    return await safeInvoke<DatabaseSavedSearch>("create_saved_search", {
+      search: toSavedSearchInput(search),
+    });
+  },
+
+  async getSavedSearches(): Promise<DatabaseSavedSearch[]> {
+    return await safeInvoke<DatabaseSavedSearch[]>("get_saved_searches");
+  },
+
+  async updateSavedSearch(
+    id: number,
+    search: Omit<SavedSearch, "id">
+  ): Promise<DatabaseSavedSearch> {
+    return await safeInvoke<DatabaseSavedSearch>("update_saved_search", {
+      id,
+      search: toSavedSearchInput(search),
+    });
+  },
+
+  async deleteSavedSearch(id: number): Promise<void> {
+    return await safeInvoke<void>("delete_saved_search", { id });
+  },
+
+  async evaluateSavedSearch(id: number): Promise<TaskWithDetails[]> {
+    return await safeInvoke<TaskWithDetails[]>("evaluate_saved_search", { id });
+  },
+
+  async getSavedSearchCounts(): Promise<SavedSearchCount[]> {
+    return await safeInvoke<SavedSearchCount[]>("get_saved_search_counts");
+  },
+};
+
+// Template operations. Titles, descriptions, subtasks and tags may contain
+// {{date}}, {{week}}, {{month}}, {{year}} and {{weekday}} placeholders.
+export const convertToFrontendTemplate = (
+  template: DatabaseTemplate
+): TaskTemplate => ({
+  id: template.id,
+  name: template.name,
+  description: template.description ?? undefined,
+  defaultTitle: template.title,
+  defaultDescription: template.task_description,
+  defaultPriority: template.priority,
+  defaultSubtasks: template.subtasks,
+  defaultProjectId: template.project_id ?? undefined,
+  defaultTags: template.tags,
+});
+
+export const templateService = {
+  async createTemplate(
+    template: Omit<TaskTemplate, "id">
+  ): Promise<DatabaseTemplate> {
+    return await safeInvoke<DatabaseTemplate>("create_template", {
+      template: {
+        name: template.name,
+        description: template.description ?? null,
+        title: template.defaultTitle,
+        task_description: template.defaultDescription,
+        priority: template.defaultPriority,
+        subtasks: template.defaultSubtasks,
+        tags: template.defaultTags,
+        project_id: template.defaultProjectId ?? null,
+      },
+    });
+  },
+
+  async listTemplates(): Promise<DatabaseTemplate[]> {
+    return await safeInvoke<DatabaseTemplate[]>("list_templates");
+  },
+
+  async deleteTemplate(id: number): Promise<void> {
+    return await safeInvoke<void>("delete_template", { id });
+  },
+
+  // Creates a task from the template and returns its id
+  async instantiateTemplate(
+    id: number,
+    dueDate?: string,
+    projectId?: number
+  ): Promise<string> {
+    return await safeInvoke<string>("instantiate_template", {
+      id,
+      dueDate: dueDate ?? null,
+      projectId: projectId ?? null,
+    });
+  },
+};
+
+// Project template operations. Due dates are stored as day offsets from an
+// anchor date (YYYY-MM-DD).
+export const projectTemplateService = {
+  async saveProjectAsTemplate(
+    projectId: number,
+    name?: string,
+    anchorDate?: string
+  ): Promise<ProjectTemplate> {
+    return await safeInvoke<ProjectTemplate>("save_project_as_template", {
+      projectId,
+      name: name ?? null,
+      anchorDate: anchorDate ?? null,
+    });
+  },
+
+  async listProjectTemplates(): Promise<ProjectTemplate[]> {
+    return await safeInvoke<ProjectTemplate[]>("list_project_templates");
+  },
+
+  async deleteProjectTemplate(id: number): Promise<void> {
+    return await safeInvoke<void>("delete_project_template", { id });
+  },
+
+  async instantiateProjectTemplate(
+    id: number,
+    folderId: number | null,
+    anchorDate?: string,
+    name?: string
+  ): Promise<DatabaseProject> {
+    return await safeInvoke<DatabaseProject>("instantiate_project_template", {
+      id,
+      folderId,
+      anchorDate: anchorDate ?? null,
+      name: name ?? null,
+    });
   },
 };
 
@@ -219,8 +728,236 @@ export const folderService = {
     return await safeInvoke<DatabaseFolder[]>("get_all_folders");
   },
 
-  async deleteFolder(folderId: number): Promise<void> {
-    return await safeInvoke<void>("delete_folder", { folderId });
+  async deleteFolder(
+    folderId: number,
+    policy?: FolderDeletePolicy
+  ): Promise<void> {
+    return await safeInvoke<void>("delete_folder", { folderId, policy });
+  },
+};
+
+// Trash operations. Deleted tasks, projects and folders stay in the trash
+// until it is emptied or they are older than the retention period.
+export type TrashKind = "task" | "project" | "folder";
+
+export interface TrashItem {
+  kind: TrashKind;
+  // Task id, or the project/folder id as a string
+  id: string;
+  name: string;
+  deleted_at: string;
+  // Tasks and projects that were deleted with it and come back with it
+  contents: number;
+}
+
+export const trashService = {
+  async listTrash(): Promise<TrashItem[]> {
+    return await safeInvoke<TrashItem[]>("list_trash");
+  },
+
+  async restoreItem(kind: TrashKind, id: string | number): Promise<void> {
+    return await safeInvoke<void>("restore_item", { kind, id: String(id) });
+  },
+
+  // Resolves to the number of rows deleted for good
+  async emptyTrash(): Promise<number> {
+    return await safeInvoke<number>("empty_trash");
+  },
+
+  async getRetentionDays(): Promise<number> {
+    return await safeInvoke<number>("get_trash_retention_days");
+  },
+
+  // 0 keeps items until the trash is emptied
+  async setRetentionDays(days: number): Promise<void> {
+    return await safeInvoke<void>("set_trash_retention_days", { days });
+  },
+};
+
+// A recorded change that can be undone, e.g. "Delete task"
+export interface Operation {
+  id: number;
+  label: string;
+  created_at: string;
+}
+
+export interface UndoState {
+  undo: Operation | null;
+  redo: Operation | null;
+}
+
+// Undo history is kept in the database, so it survives restarts. Undoing
+// something that has been changed another way since fails with a
+// `conflict` error and the operation is dropped.
+export const historyService = {
+  // Resolves to the operation undone, or null when there was none
+  async undo(): Promise<Operation | null> {
+    return await safeInvoke<Operation | null>("undo");
+  },
+
+  async redo(): Promise<Operation | null> {
+    return await safeInvoke<Operation | null>("redo");
+  },
+
+  async getUndoState(): Promise<UndoState> {
+    return await safeInvoke<UndoState>("get_undo_state");
+  },
+};
+
+export interface ProductivityStatsRequest {
+  // Inclusive `YYYY-MM-DD` local dates, spanning at most 1830 days; the last 30
+  // days by default
+  from?: string;
+  to?: string;
+}
+
+export interface CompletionSummary {
+  completed: number;
+  // Completed by the due date, or on the due day for date-only due dates
+  on_time: number;
+  late: number;
+  // null when nothing completed had a due date
+  on_time_rate: number | null;
+  average_lead_time_hours: number | null;
+}
+
+export interface CompletionBreakdown extends CompletionSummary {
+  // Project/folder id, tag or priority; null for no project or folder
+  key: string | null;
+  name: string;
+}
+
+export interface PeriodCount {
+  // The day, or the Monday starting the week
+  start: string;
+  completed: number;
+}
+
+export interface ProductivityStats extends CompletionSummary {
+  from: string;
+  to: string;
+  per_day: PeriodCount[];
+  per_week: PeriodCount[];
+  by_project: CompletionBreakdown[];
+  by_folder: CompletionBreakdown[];
+  by_tag: CompletionBreakdown[];
+  by_priority: CompletionBreakdown[];
+}
+
+export const statsService = {
+  async getProductivityStats(
+    request?: ProductivityStatsRequest
+  ): Promise<ProductivityStats> {
+    return await safeInvoke<ProductivityStats>("get_productivity_stats", {
+      request: request ?? null,
+    });
+  },
+};
+
+export type PomodoroPhase = "work" | "short-break" | "long-break";
+
+export interface PomodoroSession {
+  id: number;
+  // null without a task, or once the task is deleted for good
+  task_id: string | null;
+  phase: PomodoroPhase;
+  planned_seconds: number;
+  // null while the session is running
+  actual_seconds: number | null;
+  interrupted: boolean;
+  started_at: string;
+  ended_at: string | null;
+}
+
+// Durations are in minutes
+export interface PomodoroSettings {
+  work_duration: number;
+  short_break_duration: number;
+  long_break_duration: number;
+  sessions_until_long_break: number;
+}
+
+export interface FocusStatsRequest {
+  // Inclusive `YYYY-MM-DD` local dates, spanning at most 1830 days; the last 30
+  // days by default
+  from?: string;
+  to?: string;
+}
+
+export interface FocusTotal {
+  focus_minutes: number;
+  completed_sessions: number;
+  interrupted_sessions: number;
+}
+
+export interface FocusBreakdown extends FocusTotal {
+  // Task id, project id or the day; null for no task or project
+  key: string | null;
+  name: string;
+}
+
+// Work sessions only
+export interface FocusStats extends FocusTotal {
+  from: string;
+  to: string;
+  per_day: FocusBreakdown[];
+  per_task: FocusBreakdown[];
+  per_project: FocusBreakdown[];
+}
+
+export const pomodoroService = {
+  // Any session still running is ended as interrupted
+  async startSession(
+    phase: PomodoroPhase,
+    plannedSeconds: number,
+    taskId?: string | null
+  ): Promise<PomodoroSession> {
+    return await safeInvoke<PomodoroSession>("start_pomodoro_session", {
+      taskId: taskId ?? null,
+      phase,
+      plannedSeconds,
+    });
+  },
+
+  // `actualSeconds` defaults to the time since the session started
+  async finishSession(
+    id: number,
+    actualSeconds?: number
+  ): Promise<PomodoroSession> {
+    return await safeInvoke<PomodoroSession>("finish_pomodoro_session", {
+      id,
+      actualSeconds: actualSeconds ?? null,
+    });
+  },
+
+  async abortSession(
+    id: number,
+    actualSeconds?: number
+  ): Promise<PomodoroSession> {
+    return await safeInvoke<PomodoroSession>("abort_pomodoro_session", {
+      id,
+      actualSeconds: actualSeconds ?? null,
+    });
+  },
+
+  async getActiveSession(): Promise<PomodoroSession | null> {
+    return await safeInvoke<PomodoroSession | null>(
+      "get_active_pomodoro_session"
+    );
+  },
+
+  async getSettings(): Promise<PomodoroSettings> {
+    return await safeInvoke<PomodoroSettings>("get_pomodoro_settings");
+  },
+
+  async saveSettings(settings: PomodoroSettings): Promise<void> {
+    return await safeInvoke<void>("save_pomodoro_settings", { settings });
+  },
+
+  async getFocusStats(request?: FocusStatsRequest): Promise<FocusStats> {
+    return await safeInvoke<FocusStats>("get_focus_stats", {
+      request: request ?? null,
+    });
   },
 };
 
@@ -259,6 +996,7 @@ export const convertToFrontendTask = (taskWithDetails: TaskWithDetails) => {
     dueDate: taskWithDetails.task.due_date || "",
     priority: taskWithDetails.task.priority as "low" | "medium" | "high",
     completed: taskWithDetails.task.completed,
+    status: taskWithDetails.task.status,
     projectId: taskWithDetails.task.project_id || undefined,
     tags: taskWithDetails.tags,
     subtasks: taskWithDetails.subtasks.map((subtask) => ({
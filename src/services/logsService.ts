@@ -2,43 +2,73 @@ import { WebviewWindow } from "@tauri-apps/api/webviewWindow";
 import { isInTauriContext, safeInvoke } from "./databaseService";
 
 export interface LogEntry {
+  // Increases by one per entry; pass the last one as `after_id` to poll
+  id: number;
   timestamp: string;
-  level: string;
+  level: "TRACE" | "DEBUG" | "INFO" | "WARN" | "ERROR";
   message: string;
+  target?: string | null;
+}
+
+export type LogLevel = "off" | "error" | "warn" | "info" | "debug" | "trace";
+
+// Levels a single entry can have
+export type EntryLevel = Exclude<LogLevel, "off">;
+
+export interface LogFilter {
+  // Least severe level to include
+  level?: LogLevel;
+  // Matched anywhere in the target, case-insensitively
   target?: string;
+  // RFC 3339 timestamps
+  since?: string;
+  until?: string;
+  after_id?: number;
+  // Returns the newest `limit` entries
+  limit?: number;
 }
 
 export const logsService = {
-  // Get all application logs
-  async getLogs(): Promise<LogEntry[]> {
+  // Backend log entries, oldest first. The backend keeps the most recent
+  // entries in memory; older ones are in ~/.ultralist/logs.
+  async getLogs(filter?: LogFilter): Promise<LogEntry[]> {
     try {
-      return await safeInvoke<LogEntry[]>("get_application_logs");
+      return await safeInvoke<LogEntry[]>("get_application_logs", {
+        filter: filter ?? null,
+      });
     } catch (error) {
       console.error("Failed to get logs:", error);
-      // Return diagnostic logs instead of assuming web mode
-      return [
-        {
-          timestamp: new Date().toISOString(),
-          level: "INFO",
-          message: "Application started - attempting to retrieve Tauri logs",
-        },
-        {
-          timestamp: new Date().toISOString(),
-          level: "WARN",
-          message: `Failed to retrieve Tauri logs: ${
-            error instanceof Error ? error.message : String(error)
-          }`,
-        },
-        {
-          timestamp: new Date().toISOString(),
-          level: "INFO",
-          message: `Tauri context check: invoke available = ${
-            typeof window !== "undefined" &&
-            typeof (window as any).invoke === "function"
-          }`,
-        },
-      ];
+      return [];
+    }
+  },
+
+  // Records a frontend line in the backend log, under the `react` target or
+  // `react::<target>`, so it shows up next to backend entries. Also written to
+  // the console, which is all that happens outside Tauri.
+  async log(level: EntryLevel, message: string, target?: string): Promise<void> {
+    const consoleMethod = level === "trace" ? "debug" : level;
+    console[consoleMethod](target ? `[${target}] ${message}` : message);
+    if (!isInTauriContext()) {
+      return;
     }
+    try {
+      await safeInvoke<void>("write_frontend_log", {
+        level,
+        message,
+        target: target ?? null,
+      });
+    } catch (error) {
+      console.error("Failed to write log:", error);
+    }
+  },
+
+  async getLogLevel(): Promise<LogLevel> {
+    return await safeInvoke<LogLevel>("get_log_level");
+  },
+
+  // Applies immediately and is remembered across restarts
+  async setLogLevel(level: LogLevel): Promise<void> {
+    return await safeInvoke<void>("set_log_level", { level });
   },
 
   // Open logs in a new window
@@ -28,6 +28,7 @@ export interface Task {
   dueDate: string;
   priority: "low" | "medium" | "high";
   completed: boolean;
+  status?: TaskStatus;
   subtasks: Subtask[];
   projectId?: number;
   tags: string[];